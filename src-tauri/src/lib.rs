@@ -1,3 +1,5 @@
+use tauri::Manager;
+
 mod library;
 mod extensions;
 
@@ -13,13 +15,61 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            // Open the library database before any command can reach it
+            let app_data_dir = app.path().app_data_dir()?;
+            app.manage(library::db::Database::open(&app_data_dir)?);
+            Ok(())
+        })
+        .manage(library::progress::ImportRegistry::default())
+        .manage(library::history::IncognitoMode::default())
+        .manage(library::stored_archive::ArchiveCache::default())
+        .register_asynchronous_uri_scheme_protocol(
+            library::stored_archive::PAGE_PROTOCOL,
+            |ctx, request, responder| {
+                // Read pages off the main thread so a large archive doesn't stall the webview
+                let app = ctx.app_handle().clone();
+                tauri::async_runtime::spawn_blocking(move || {
+                    responder.respond(library::stored_archive::serve_page(&app, &request));
+                });
+            },
+        )
         .invoke_handler(tauri::generate_handler![
             greet,
 
             // Library Commands
+            library::get_library,
+            library::query_library,
+            library::get_manga,
+            library::add_manga,
+            library::update_manga,
+            library::update_manga_metadata,
+            library::remove_manga,
             library::import_manga_folder,
             library::import_manga_cbz,
-            library::delete_manga,
+            library::import_manga_archive,
+            library::import_manga_series,
+            library::import_manga_pdf,
+            library::import_manga_epub,
+            library::cancel_import,
+            library::set_manga_cover,
+            library::get_manga_thumbnail,
+            library::list_pages,
+            library::set_page_order,
+            library::record_reading_progress,
+            library::get_reading_progress,
+            library::record_history,
+            library::get_history,
+            library::delete_history_entry,
+            library::delete_history_range,
+            library::set_incognito,
+            library::get_incognito,
+            library::get_categories,
+            library::create_category,
+            library::rename_category,
+            library::reorder_categories,
+            library::delete_category,
+            library::open_manga,
 
             // Extensions Commands
             extensions::validate_extension_file,
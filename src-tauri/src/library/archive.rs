@@ -0,0 +1,572 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use sevenz_rust::{Password, SevenZReader};
+use unrar::Archive as RarArchive;
+use zip::ZipArchive;
+use crate::library::comicinfo::{self, ComicInfo};
+use crate::library::images::{self, ImageFormat};
+use crate::library::progress::ImportTracker;
+
+// Comic archive formats we can import (.cbz, .cbr, .cb7, .cbt)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Rar,
+    SevenZip,
+    Tar,
+}
+
+impl ArchiveFormat {
+    // Detect the format from the file's magic bytes, falling back to its extension
+    pub fn detect(path: &Path) -> Result<ArchiveFormat, String> {
+        let mut header = [0u8; 262];
+        let mut file = fs::File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+        let read = read_up_to(&mut file, &mut header)
+            .map_err(|e| format!("Failed to read archive header: {}", e))?;
+        let header = &header[..read];
+
+        if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            return Ok(ArchiveFormat::Zip);
+        }
+        if header.starts_with(b"Rar!\x1a\x07") {
+            return Ok(ArchiveFormat::Rar);
+        }
+        if header.starts_with(b"7z\xbc\xaf\x27\x1c") {
+            return Ok(ArchiveFormat::SevenZip);
+        }
+        if header.len() >= 262 && &header[257..262] == b"ustar" {
+            return Ok(ArchiveFormat::Tar);
+        }
+
+        // Old-style tar files have no magic, so trust the extension as a last resort
+        ArchiveFormat::from_extension(path)
+            .ok_or_else(|| format!("Unsupported archive format: {}", path.display()))
+    }
+
+    // The format a comic archive's extension names; EPUBs are ZIPs too but not comic archives
+    pub fn from_extension(path: &Path) -> Option<ArchiveFormat> {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "cbz" | "zip" => Some(ArchiveFormat::Zip),
+            "cbr" | "rar" => Some(ArchiveFormat::Rar),
+            "cb7" | "7z" => Some(ArchiveFormat::SevenZip),
+            "cbt" | "tar" => Some(ArchiveFormat::Tar),
+            _ => None,
+        }
+    }
+}
+
+// Receives the file entries of an archive in storage order
+pub trait EntryVisitor {
+    // Whether the entry's data is needed; unwanted entries are skipped cheaply
+    fn wants(&self, name: &Path) -> bool;
+
+    fn visit(&mut self, name: &Path, data: &mut dyn Read) -> Result<(), String>;
+}
+
+// Sequential reader over the file entries of a comic archive
+pub trait ArchiveReader {
+    fn for_each_file(&mut self, visitor: &mut dyn EntryVisitor) -> Result<(), String>;
+}
+
+// Open an archive with the reader matching its detected format
+pub fn open_archive(path: &Path) -> Result<Box<dyn ArchiveReader>, String> {
+    let format = ArchiveFormat::detect(path)?;
+    let reader: Box<dyn ArchiveReader> = match format {
+        ArchiveFormat::Zip => Box::new(ZipReader::open(path)?),
+        ArchiveFormat::Rar => Box::new(RarReader { path: path.to_path_buf() }),
+        ArchiveFormat::SevenZip => Box::new(SevenZipReader::open(path)?),
+        ArchiveFormat::Tar => Box::new(TarReader::open(path)?),
+    };
+    Ok(reader)
+}
+
+// Extract the image pages of any supported archive to a directory,
+// returning the archive's ComicInfo.xml metadata if it has any
+pub fn extract_archive(
+    archive_path: &str,
+    destination: &Path,
+    tracker: &mut ImportTracker,
+) -> Result<Option<ComicInfo>, String> {
+    let mut reader = open_archive(Path::new(archive_path))?;
+    let mut extractor = PageExtractor {
+        destination,
+        comic_info: None,
+        tracker,
+    };
+    reader.for_each_file(&mut extractor)?;
+
+    // Broken metadata shouldn't fail an otherwise good import
+    let comic_info = extractor.comic_info.and_then(|xml| match comicinfo::parse_comic_info(&xml) {
+        Ok(comic_info) => Some(comic_info),
+        Err(err) => {
+            println!("Ignoring ComicInfo.xml in {}: {}", archive_path, err);
+            None
+        }
+    });
+
+    Ok(comic_info)
+}
+
+// Shared extraction pipeline: safe names only, images (by content) only, keeping the
+// archive's folders so they become chapters
+struct PageExtractor<'a> {
+    destination: &'a Path,
+    comic_info: Option<String>,
+    tracker: &'a mut ImportTracker,
+}
+
+impl EntryVisitor for PageExtractor<'_> {
+    // Every file is looked at, since pages are recognised by their content
+    fn wants(&self, name: &Path) -> bool {
+        enclosed_name(name).is_some()
+    }
+
+    fn visit(&mut self, name: &Path, data: &mut dyn Read) -> Result<(), String> {
+        self.tracker.check_cancelled()?;
+
+        // Keep the metadata in memory instead of writing it out as a page
+        if comicinfo::is_comic_info(name) {
+            let mut xml = String::new();
+            data.read_to_string(&mut xml)
+                .map_err(|e| format!("Failed to read ComicInfo.xml: {}", e))?;
+            self.comic_info = Some(xml);
+            return Ok(());
+        }
+
+        let relative_path = match enclosed_name(name) {
+            Some(relative_path) => relative_path,
+            None => return Ok(()),
+        };
+        let file_name = match relative_path.file_name() {
+            Some(file_name) => file_name,
+            None => return Ok(()),
+        };
+
+        // Sniff the page format; the extension may be wrong or missing
+        let header = images::read_header(data)
+            .map_err(|e| format!("Failed to read file in archive: {}", e))?;
+        let format = match ImageFormat::sniff(&header) {
+            Some(format) => format,
+            None => {
+                // 7z entries share one stream, so the rest still has to be read
+                io::copy(data, &mut io::sink())
+                    .map_err(|e| format!("Failed to read file in archive: {}", e))?;
+                self.tracker
+                    .file_skipped(&name.to_string_lossy(), "not a recognised image format");
+                return Ok(());
+            }
+        };
+
+        // Create the destination path, with an extension matching the content
+        let dest_dir = match relative_path.parent() {
+            Some(parent) => self.destination.join(parent),
+            None => self.destination.to_path_buf(),
+        };
+        fs::create_dir_all(&dest_dir)
+            .map_err(|e| format!("Failed to create output directory: {}", e))?;
+        let dest_path = unique_path(&dest_dir.join(format.file_name(Path::new(file_name))));
+
+        // Create a file to write to
+        let mut outfile = fs::File::create(&dest_path)
+            .map_err(|e| format!("Failed to create output file: {}", e))?;
+
+        // Copy the file data
+        let bytes = io::copy(&mut header.as_slice().chain(data), &mut outfile)
+            .map_err(|e| format!("Failed to write file data: {}", e))?;
+        self.tracker.file_written(&name.to_string_lossy(), bytes);
+
+        Ok(())
+    }
+}
+
+struct ZipReader {
+    archive: ZipArchive<fs::File>,
+}
+
+impl ZipReader {
+    fn open(path: &Path) -> Result<ZipReader, String> {
+        let file = fs::File::open(path).map_err(|e| format!("Failed to open CBZ file: {}", e))?;
+        let archive = ZipArchive::new(file)
+            .map_err(|e| format!("Failed to read CBZ as ZIP archive: {}", e))?;
+        Ok(ZipReader { archive })
+    }
+}
+
+impl ArchiveReader for ZipReader {
+    fn for_each_file(&mut self, visitor: &mut dyn EntryVisitor) -> Result<(), String> {
+        for i in 0..self.archive.len() {
+            let mut file = self
+                .archive
+                .by_index(i)
+                .map_err(|e| format!("Failed to access file in archive: {}", e))?;
+
+            let name = PathBuf::from(file.name());
+            if file.is_dir() || !visitor.wants(&name) {
+                continue;
+            }
+
+            visitor.visit(&name, &mut file)?;
+        }
+        Ok(())
+    }
+}
+
+struct RarReader {
+    path: PathBuf,
+}
+
+impl ArchiveReader for RarReader {
+    fn for_each_file(&mut self, visitor: &mut dyn EntryVisitor) -> Result<(), String> {
+        let mut archive = RarArchive::new(&self.path)
+            .open_for_processing()
+            .map_err(|e| format!("Failed to read CBR as RAR archive: {}", e))?;
+
+        // Walk the archive headers, reading or skipping each entry
+        while let Some(header) = archive
+            .read_header()
+            .map_err(|e| format!("Failed to access file in archive: {}", e))?
+        {
+            let name = header.entry().filename.clone();
+            if !header.entry().is_file() || !visitor.wants(&name) {
+                archive = header
+                    .skip()
+                    .map_err(|e| format!("Failed to skip file in archive: {}", e))?;
+                continue;
+            }
+
+            let (data, next) = header
+                .read()
+                .map_err(|e| format!("Failed to read file in archive: {}", e))?;
+            visitor.visit(&name, &mut data.as_slice())?;
+            archive = next;
+        }
+        Ok(())
+    }
+}
+
+struct SevenZipReader {
+    archive: SevenZReader<fs::File>,
+}
+
+impl SevenZipReader {
+    fn open(path: &Path) -> Result<SevenZipReader, String> {
+        let archive = SevenZReader::open(path, Password::empty())
+            .map_err(|e| format!("Failed to read CB7 as 7z archive: {}", e))?;
+        Ok(SevenZipReader { archive })
+    }
+}
+
+impl ArchiveReader for SevenZipReader {
+    fn for_each_file(&mut self, visitor: &mut dyn EntryVisitor) -> Result<(), String> {
+        // The visitor's error is kept aside since 7z callbacks must return its own error type
+        let mut visit_error = None;
+        let result = self.archive.for_each_entries(|entry, data| {
+            let name = PathBuf::from(entry.name());
+            if entry.is_directory() || !visitor.wants(&name) {
+                // Entries share one decoder stream, so skipped data still has to be consumed
+                io::copy(data, &mut io::sink())?;
+                return Ok(true);
+            }
+
+            match visitor.visit(&name, data) {
+                Ok(()) => Ok(true),
+                Err(err) => {
+                    visit_error = Some(err);
+                    Ok(false)
+                }
+            }
+        });
+
+        if let Some(err) = visit_error {
+            return Err(err);
+        }
+        result.map_err(|e| format!("Failed to access file in archive: {}", e))
+    }
+}
+
+struct TarReader {
+    archive: tar::Archive<fs::File>,
+}
+
+impl TarReader {
+    fn open(path: &Path) -> Result<TarReader, String> {
+        let file = fs::File::open(path).map_err(|e| format!("Failed to open CBT file: {}", e))?;
+        Ok(TarReader {
+            archive: tar::Archive::new(file),
+        })
+    }
+}
+
+impl ArchiveReader for TarReader {
+    fn for_each_file(&mut self, visitor: &mut dyn EntryVisitor) -> Result<(), String> {
+        let entries = self
+            .archive
+            .entries()
+            .map_err(|e| format!("Failed to read CBT as tar archive: {}", e))?;
+
+        for entry in entries {
+            let mut entry = entry.map_err(|e| format!("Failed to access file in archive: {}", e))?;
+            let name = entry
+                .path()
+                .map_err(|e| format!("Failed to read file name in archive: {}", e))?
+                .into_owned();
+
+            if !entry.header().entry_type().is_file() || !visitor.wants(&name) {
+                continue;
+            }
+
+            visitor.visit(&name, &mut entry)?;
+        }
+        Ok(())
+    }
+}
+
+// Reject archive entry names that are absolute or escape the destination,
+// mirroring `ZipFile::enclosed_name` for every format; "." and ".." are resolved
+pub fn enclosed_name(name: &Path) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in name.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => return None,
+            Component::ParentDir => {
+                if !path.pop() {
+                    return None;
+                }
+            }
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+        }
+    }
+    Some(path)
+}
+
+// A free path for a file, adding " (2)", " (3)"… to the name instead of overwriting.
+// Entries are visited in archive order, so the same archive always gets the same names
+pub fn unique_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    (2..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap()
+}
+
+// Fill as much of the buffer as the file allows
+fn read_up_to(file: &mut fs::File, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buffer.len() {
+        match file.read(&mut buffer[read..])? {
+            0 => break,
+            n => read += n,
+        }
+    }
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    // Names and contents every test archive holds, in storage order
+    const ENTRIES: [(&str, &[u8]); 3] = [
+        ("001.png", b"\x89PNG\r\n\x1a\npage one"),
+        ("Chapter 1/002.jpg", b"\xff\xd8\xffpage two"),
+        ("notes.txt", b"not a page"),
+    ];
+
+    // Collects every entry except the ones named `notes.txt`
+    #[derive(Default)]
+    struct Collector {
+        entries: Vec<(PathBuf, Vec<u8>)>,
+    }
+
+    impl EntryVisitor for Collector {
+        fn wants(&self, name: &Path) -> bool {
+            name.file_name().is_some_and(|file_name| file_name != "notes.txt")
+        }
+
+        fn visit(&mut self, name: &Path, data: &mut dyn Read) -> Result<(), String> {
+            let mut bytes = Vec::new();
+            data.read_to_end(&mut bytes).map_err(|err| err.to_string())?;
+            self.entries.push((name.to_path_buf(), bytes));
+            Ok(())
+        }
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dokusho-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // Read an archive through `open_archive` and check the wanted entries came through intact
+    fn assert_reads_entries(path: &Path, format: ArchiveFormat) {
+        assert_eq!(ArchiveFormat::detect(path).unwrap(), format);
+
+        let mut collector = Collector::default();
+        open_archive(path).unwrap().for_each_file(&mut collector).unwrap();
+        collector.entries.sort();
+
+        let expected: Vec<(PathBuf, Vec<u8>)> = ENTRIES[..2]
+            .iter()
+            .map(|(name, data)| (PathBuf::from(name), data.to_vec()))
+            .collect();
+        assert_eq!(collector.entries, expected);
+    }
+
+    #[test]
+    fn reads_zip() {
+        let dir = test_dir("zip");
+        let path = dir.join("test.cbz");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        writer.add_directory("Chapter 1/", zip::write::SimpleFileOptions::default()).unwrap();
+        for (name, data) in ENTRIES {
+            writer.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert_reads_entries(&path, ArchiveFormat::Zip);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reads_rar() {
+        let dir = test_dir("rar");
+        let path = dir.join("test.cbr");
+        fs::write(&path, stored_rar(&ENTRIES)).unwrap();
+
+        assert_reads_entries(&path, ArchiveFormat::Rar);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reads_seven_zip() {
+        let dir = test_dir("7z");
+        let source = dir.join("source");
+        for (name, data) in ENTRIES {
+            fs::create_dir_all(source.join(name).parent().unwrap()).unwrap();
+            fs::write(source.join(name), data).unwrap();
+        }
+        let path = dir.join("test.cb7");
+        sevenz_rust::compress_to_path(&source, &path).unwrap();
+
+        assert_reads_entries(&path, ArchiveFormat::SevenZip);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reads_tar() {
+        let dir = test_dir("tar");
+        let path = dir.join("test.cbt");
+        let mut builder = tar::Builder::new(fs::File::create(&path).unwrap());
+        for (name, data) in ENTRIES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, data).unwrap();
+        }
+        builder.finish().unwrap();
+
+        assert_reads_entries(&path, ArchiveFormat::Tar);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn detect_falls_back_to_extension() {
+        let dir = test_dir("detect");
+        let path = dir.join("old.cbt");
+        fs::write(&path, b"no magic here").unwrap();
+        assert_eq!(ArchiveFormat::detect(&path).unwrap(), ArchiveFormat::Tar);
+
+        let path = dir.join("notes.txt");
+        fs::write(&path, b"no magic here").unwrap();
+        assert!(ArchiveFormat::detect(&path).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn enclosed_name_rejects_escapes() {
+        assert_eq!(enclosed_name(Path::new("../evil.jpg")), None);
+        assert_eq!(enclosed_name(Path::new("Chapter 1/../../evil.jpg")), None);
+        assert_eq!(enclosed_name(Path::new("/etc/passwd")), None);
+        assert_eq!(
+            enclosed_name(Path::new("Chapter 1/./extra/../001.jpg")),
+            Some(PathBuf::from("Chapter 1/001.jpg"))
+        );
+    }
+
+    #[test]
+    fn unique_path_numbers_taken_names() {
+        let dir = test_dir("unique");
+        let path = dir.join("001.jpg");
+        assert_eq!(unique_path(&path), path);
+
+        fs::write(&path, b"").unwrap();
+        assert_eq!(unique_path(&path), dir.join("001 (2).jpg"));
+        fs::write(dir.join("001 (2).jpg"), b"").unwrap();
+        assert_eq!(unique_path(&path), dir.join("001 (3).jpg"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // A RAR 4 archive with the files stored uncompressed, since there's no RAR writer to use
+    fn stored_rar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        // Header CRCs are the low half of the CRC-32 over everything after the CRC field
+        fn block(kind: u8, flags: u16, fields: &[u8]) -> Vec<u8> {
+            let mut header = vec![kind];
+            header.extend_from_slice(&flags.to_le_bytes());
+            header.extend_from_slice(&(fields.len() as u16 + 7).to_le_bytes());
+            header.extend_from_slice(fields);
+            let mut block = (crc32(&header) as u16).to_le_bytes().to_vec();
+            block.extend(header);
+            block
+        }
+
+        let mut rar = b"Rar!\x1a\x07\x00".to_vec();
+        rar.extend(block(0x73, 0, &[0; 6]));
+        for (name, data) in entries {
+            let mut fields = Vec::new();
+            fields.extend_from_slice(&(data.len() as u32).to_le_bytes()); // packed size
+            fields.extend_from_slice(&(data.len() as u32).to_le_bytes()); // unpacked size
+            fields.push(2); // host OS: Windows
+            fields.extend_from_slice(&crc32(data).to_le_bytes());
+            fields.extend_from_slice(&0x5a21_0000u32.to_le_bytes()); // DOS time, 2025-01-01
+            fields.push(20); // version needed to extract
+            fields.push(0x30); // method: store
+            fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            fields.extend_from_slice(&0x20u32.to_le_bytes()); // attributes: archive
+            fields.extend_from_slice(name.replace('/', "\\").as_bytes());
+            // Flag 0x8000: the packed data follows the header
+            rar.extend(block(0x74, 0x8000, &fields));
+            rar.extend_from_slice(data);
+        }
+        rar.extend(block(0x7b, 0x4000, &[]));
+        rar
+    }
+
+    fn crc32(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for byte in data {
+            crc ^= *byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+            }
+        }
+        !crc
+    }
+}
@@ -0,0 +1,115 @@
+use tauri::{AppHandle, Manager, Runtime};
+use std::collections::HashSet;
+use nanoid::nanoid;
+use crate::library::db::Database;
+use crate::library::models::Category;
+
+pub fn get_categories<R: Runtime>(app: AppHandle<R>) -> Result<Vec<Category>, String> {
+    app.state::<Database>().categories()
+}
+
+// Add a category at the end of the list
+pub fn create_category<R: Runtime>(app: AppHandle<R>, name: &str) -> Result<Category, String> {
+    let database = app.state::<Database>();
+    let name = validate_name(&database.categories()?, None, name)?;
+
+    let category = Category {
+        id: nanoid!(10),
+        name,
+        manga_count: 0,
+    };
+    database.add_category(&category)?;
+    Ok(category)
+}
+
+pub fn rename_category<R: Runtime>(
+    app: AppHandle<R>,
+    category_id: &str,
+    name: &str,
+) -> Result<Category, String> {
+    let database = app.state::<Database>();
+    let categories = database.categories()?;
+    let mut category = categories
+        .iter()
+        .find(|category| category.id == category_id)
+        .cloned()
+        .ok_or_else(|| format!("Category {} not found", category_id))?;
+
+    category.name = validate_name(&categories, Some(category_id), name)?;
+    database.rename_category(category_id, &category.name)?;
+    Ok(category)
+}
+
+// Put the categories in the given order; every category has to be listed exactly once
+pub fn reorder_categories<R: Runtime>(
+    app: AppHandle<R>,
+    category_ids: Vec<String>,
+) -> Result<Vec<Category>, String> {
+    let database = app.state::<Database>();
+    let existing: HashSet<String> =
+        database.categories()?.into_iter().map(|category| category.id).collect();
+    let requested: HashSet<String> = category_ids.iter().cloned().collect();
+    if requested.len() != category_ids.len() || requested != existing {
+        return Err("Category order must list every category exactly once".to_string());
+    }
+
+    database.reorder_categories(&category_ids)?;
+    database.categories()
+}
+
+// Delete a category; manga in it must be moved to `reassign_to` first
+pub fn delete_category<R: Runtime>(
+    app: AppHandle<R>,
+    category_id: &str,
+    reassign_to: Option<String>,
+) -> Result<(), String> {
+    let database = app.state::<Database>();
+    let categories = database.categories()?;
+    if !categories.iter().any(|category| category.id == category_id) {
+        return Err(format!("Category {} not found", category_id));
+    }
+    // Manga always belong to some category, so the last one stays
+    if categories.len() == 1 {
+        return Err("Cannot delete the only category".to_string());
+    }
+
+    if let Some(target) = reassign_to.as_deref() {
+        if target == category_id {
+            return Err("Cannot move manga into the category being deleted".to_string());
+        }
+        if !categories.iter().any(|category| category.id == target) {
+            return Err(format!("Category {} not found", target));
+        }
+    }
+
+    database.delete_category(category_id, reassign_to.as_deref())
+}
+
+// Reject manga being filed under a category that doesn't exist
+pub fn ensure_category<R: Runtime>(app: &AppHandle<R>, category_id: &str) -> Result<(), String> {
+    if !app.state::<Database>().category_exists(category_id)? {
+        return Err(format!("Category {} not found", category_id));
+    }
+    Ok(())
+}
+
+// Trimmed, non-empty and not already used by another category, ignoring case
+fn validate_name(
+    categories: &[Category],
+    category_id: Option<&str>,
+    name: &str,
+) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Category name cannot be empty".to_string());
+    }
+
+    let taken = categories.iter().any(|category| {
+        Some(category.id.as_str()) != category_id
+            && category.name.to_lowercase() == name.to_lowercase()
+    });
+    if taken {
+        return Err(format!("A category named {} already exists", name));
+    }
+    Ok(name.to_string())
+}
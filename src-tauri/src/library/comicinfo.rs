@@ -0,0 +1,136 @@
+use std::path::Path;
+use roxmltree::Document;
+use crate::library::models::MangaMetadata;
+
+// Metadata carried by a ComicRack-style ComicInfo.xml
+pub struct ComicInfo {
+    pub title: Option<String>,
+    pub metadata: MangaMetadata,
+}
+
+impl ComicInfo {
+    // Series name first since <Title> usually names a single issue
+    pub fn display_title(&self) -> Option<String> {
+        self.metadata.series.clone().or_else(|| self.title.clone())
+    }
+}
+
+// Whether an archive entry is the ComicInfo.xml metadata file
+pub fn is_comic_info(name: &Path) -> bool {
+    name.file_name()
+        .is_some_and(|file_name| file_name.to_string_lossy().eq_ignore_ascii_case("ComicInfo.xml"))
+}
+
+pub fn parse_comic_info(xml: &str) -> Result<ComicInfo, String> {
+    let document =
+        Document::parse(xml).map_err(|e| format!("Invalid ComicInfo.xml: {}", e))?;
+    let root = document.root_element();
+
+    // Child element text by tag name, ignoring empty elements
+    let field = |tag: &str| {
+        root.children()
+            .find(|node| node.tag_name().name() == tag)
+            .and_then(|node| node.text())
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+    };
+    // Comma separated people, genre and tag lists
+    let list = |tag: &str| {
+        field(tag)
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|item| item.trim().to_string())
+                    .filter(|item| !item.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let reading_direction = match field("Manga").as_deref() {
+        Some("YesAndRightToLeft") => Some("right-to-left".to_string()),
+        Some("No") => Some("left-to-right".to_string()),
+        _ => None,
+    };
+
+    Ok(ComicInfo {
+        title: field("Title"),
+        metadata: MangaMetadata {
+            authors: list("Writer"),
+            artists: list("Penciller"),
+            reading_direction,
+            series: field("Series"),
+            alternative_titles: field("AlternateSeries").into_iter().collect(),
+            number: field("Number"),
+            volume: field("Volume").and_then(|volume| volume.parse().ok()),
+            description: field("Summary"),
+            genres: list("Genre"),
+            tags: list("Tags"),
+            language: field("LanguageISO"),
+            age_rating: field("AgeRating").filter(|rating| rating != "Unknown"),
+            ..MangaMetadata::default()
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMIC_INFO: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<ComicInfo xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <Title>The Beginning</Title>
+  <Series>Blue Period</Series>
+  <Number>12.5</Number>
+  <Volume>3</Volume>
+  <Writer>Tsubasa Yamaguchi, </Writer>
+  <Penciller>Tsubasa Yamaguchi,Assistant One</Penciller>
+  <Genre>Drama, Slice of Life</Genre>
+  <Manga>YesAndRightToLeft</Manga>
+  <AgeRating>Unknown</AgeRating>
+  <Summary>   </Summary>
+</ComicInfo>"#;
+
+    #[test]
+    fn parses_series_numbering_and_people() {
+        let info = parse_comic_info(COMIC_INFO).unwrap();
+        let metadata = &info.metadata;
+
+        assert_eq!(info.title.as_deref(), Some("The Beginning"));
+        assert_eq!(info.display_title().as_deref(), Some("Blue Period"));
+        assert_eq!(metadata.number.as_deref(), Some("12.5"));
+        assert_eq!(metadata.volume, Some(3));
+        assert_eq!(metadata.authors, ["Tsubasa Yamaguchi"]);
+        assert_eq!(metadata.artists, ["Tsubasa Yamaguchi", "Assistant One"]);
+        assert_eq!(metadata.genres, ["Drama", "Slice of Life"]);
+        assert_eq!(metadata.reading_direction.as_deref(), Some("right-to-left"));
+        assert_eq!(metadata.age_rating, None);
+        assert_eq!(metadata.description, None);
+    }
+
+    #[test]
+    fn falls_back_to_the_issue_title() {
+        let info = parse_comic_info(
+            "<ComicInfo><Title>One-shot</Title><Volume>two</Volume><Manga>Yes</Manga></ComicInfo>",
+        )
+        .unwrap();
+
+        assert_eq!(info.display_title().as_deref(), Some("One-shot"));
+        assert_eq!(info.metadata.volume, None);
+        assert_eq!(info.metadata.reading_direction, None);
+        assert!(info.metadata.authors.is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_xml() {
+        assert!(parse_comic_info("<ComicInfo><Series>Unclosed</ComicInfo>").is_err());
+        assert!(parse_comic_info("").is_err());
+    }
+
+    #[test]
+    fn recognises_comic_info_entries() {
+        assert!(is_comic_info(Path::new("ComicInfo.xml")));
+        assert!(is_comic_info(Path::new("Vol 01/comicinfo.XML")));
+        assert!(!is_comic_info(Path::new("ComicInfo.xml.bak")));
+    }
+}
@@ -1,18 +1,216 @@
 use tauri::{AppHandle, Runtime};
 use crate::library::models::MangaInput;
-use crate::library::models::Manga;
-use crate::library::service;
+use crate::library::models::{
+    Category, CoverSource, HistoryEntry, ImportResult, Library, LibraryPage, LibraryQuery, Manga,
+    MangaUpdate, MetadataUpdate, OnlineMangaInput, PageList, ReadingPosition, ReadingProgress,
+};
+use crate::library::{categories, covers, history, reading, service};
+use crate::library::thumbnails::ThumbnailSize;
 
 #[tauri::command]
-pub fn delete_manga(path: &str) -> Result<(), String> {
-    service::delete_manga(path)
+pub fn get_library<R: Runtime>(app: AppHandle<R>) -> Result<Library, String> {
+    service::get_library(app)
+}
+
+#[tauri::command]
+pub fn query_library<R: Runtime>(
+    app: AppHandle<R>,
+    query: LibraryQuery,
+) -> Result<LibraryPage, String> {
+    service::query_library(app, query)
+}
+
+#[tauri::command]
+pub fn get_manga<R: Runtime>(app: AppHandle<R>, manga_id: &str) -> Result<Manga, String> {
+    service::get_manga(app, manga_id)
+}
+
+#[tauri::command]
+pub fn add_manga<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: OnlineMangaInput,
+) -> Result<Manga, String> {
+    service::add_manga(app, manga_input)
+}
+
+#[tauri::command]
+pub fn update_manga<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    changes: MangaUpdate,
+) -> Result<Manga, String> {
+    service::update_manga(app, manga_id, changes)
+}
+
+// Imported files go with the entry; linked folders and archives are left alone
+#[tauri::command]
+pub fn remove_manga<R: Runtime>(app: AppHandle<R>, manga_id: &str) -> Result<(), String> {
+    service::delete_manga(app, manga_id)
+}
+
+#[tauri::command]
+pub fn open_manga<R: Runtime>(app: AppHandle<R>, manga_id: &str) -> Result<Manga, String> {
+    service::open_manga(app, manga_id)
+}
+
+// Reading and re-encoding images is slow, so these run off the main thread
+#[tauri::command]
+pub async fn set_manga_cover<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    source: CoverSource,
+) -> Result<Manga, String> {
+    covers::set_manga_cover(app, manga_id, source)
+}
+
+#[tauri::command]
+pub async fn get_manga_thumbnail<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    size: ThumbnailSize,
+) -> Result<String, String> {
+    service::get_manga_thumbnail(app, manga_id, size)
+}
+
+#[tauri::command]
+pub async fn list_pages<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    chapter_id: &str,
+) -> Result<PageList, String> {
+    service::list_pages(app, manga_id, chapter_id)
+}
+
+#[tauri::command]
+pub fn set_page_order<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    chapter_id: &str,
+    pages: Vec<String>,
+) -> Result<(), String> {
+    service::set_page_order(app, manga_id, chapter_id, pages)
+}
+
+#[tauri::command]
+pub fn record_reading_progress<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    chapter_id: String,
+    page: u32,
+    scroll_offset: Option<f64>,
+) -> Result<ReadingProgress, String> {
+    let position = ReadingPosition {
+        chapter_id,
+        page,
+        scroll_offset: scroll_offset.unwrap_or_default(),
+    };
+    reading::record_reading_progress(app, manga_id, position)
+}
+
+#[tauri::command]
+pub fn get_reading_progress<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+) -> Result<ReadingProgress, String> {
+    reading::get_reading_progress(app, manga_id)
+}
+
+#[tauri::command]
+pub fn record_history<R: Runtime>(
+    app: AppHandle<R>,
+    entry: HistoryEntry,
+) -> Result<Option<HistoryEntry>, String> {
+    history::record_history(app, entry)
+}
+
+#[tauri::command]
+pub fn get_history<R: Runtime>(
+    app: AppHandle<R>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Vec<HistoryEntry>, String> {
+    history::get_history(app, limit, offset)
+}
+
+#[tauri::command]
+pub fn delete_history_entry<R: Runtime>(app: AppHandle<R>, id: i64) -> Result<(), String> {
+    history::delete_history_entry(app, id)
+}
+
+#[tauri::command]
+pub fn delete_history_range<R: Runtime>(
+    app: AppHandle<R>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<usize, String> {
+    history::delete_history_range(app, from, to)
+}
+
+#[tauri::command]
+pub fn set_incognito<R: Runtime>(app: AppHandle<R>, enabled: bool) {
+    history::set_incognito(app, enabled)
+}
+
+#[tauri::command]
+pub fn get_incognito<R: Runtime>(app: AppHandle<R>) -> bool {
+    history::get_incognito(app)
+}
+
+#[tauri::command]
+pub fn update_manga_metadata<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    changes: MetadataUpdate,
+) -> Result<Manga, String> {
+    service::update_manga_metadata(app, manga_id, changes)
+}
+
+#[tauri::command]
+pub fn get_categories<R: Runtime>(app: AppHandle<R>) -> Result<Vec<Category>, String> {
+    categories::get_categories(app)
+}
+
+#[tauri::command]
+pub fn create_category<R: Runtime>(app: AppHandle<R>, name: &str) -> Result<Category, String> {
+    categories::create_category(app, name)
+}
+
+#[tauri::command]
+pub fn rename_category<R: Runtime>(
+    app: AppHandle<R>,
+    category_id: &str,
+    name: &str,
+) -> Result<Category, String> {
+    categories::rename_category(app, category_id, name)
+}
+
+#[tauri::command]
+pub fn reorder_categories<R: Runtime>(
+    app: AppHandle<R>,
+    category_ids: Vec<String>,
+) -> Result<Vec<Category>, String> {
+    categories::reorder_categories(app, category_ids)
+}
+
+#[tauri::command]
+pub fn delete_category<R: Runtime>(
+    app: AppHandle<R>,
+    category_id: &str,
+    reassign_to: Option<String>,
+) -> Result<(), String> {
+    categories::delete_category(app, category_id, reassign_to)
+}
+
+#[tauri::command]
+pub fn cancel_import<R: Runtime>(app: AppHandle<R>, import_id: &str) -> Result<(), String> {
+    service::cancel_import(app, import_id)
 }
 
 #[tauri::command]
 pub async fn import_manga_folder<R: Runtime>(
     app: AppHandle<R>,
     manga_input: MangaInput,
-) -> Result<Manga, String> {
+) -> Result<ImportResult, String> {
     service::import_manga_folder(app, manga_input)
 }
 
@@ -20,6 +218,38 @@ pub async fn import_manga_folder<R: Runtime>(
 pub async fn import_manga_cbz<R: Runtime>(
     app: AppHandle<R>,
     manga_input: MangaInput,
-) -> Result<Manga, String> {
+) -> Result<ImportResult, String> {
     service::import_manga_cbz(app, manga_input).await
+}
+
+#[tauri::command]
+pub async fn import_manga_archive<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: MangaInput,
+) -> Result<ImportResult, String> {
+    service::import_manga_archive(app, manga_input).await
+}
+
+#[tauri::command]
+pub async fn import_manga_series<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: MangaInput,
+) -> Result<ImportResult, String> {
+    service::import_manga_series(app, manga_input).await
+}
+
+#[tauri::command]
+pub async fn import_manga_pdf<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: MangaInput,
+) -> Result<ImportResult, String> {
+    service::import_manga_pdf(app, manga_input).await
+}
+
+#[tauri::command]
+pub async fn import_manga_epub<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: MangaInput,
+) -> Result<ImportResult, String> {
+    service::import_manga_epub(app, manga_input).await
 }
\ No newline at end of file
@@ -0,0 +1,158 @@
+use tauri::{AppHandle, Manager, Runtime};
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use image::codecs::jpeg::JpegEncoder;
+use crate::library::images::ImageFormat;
+use crate::library::models::{CoverCrop, CoverSource, Manga};
+use crate::library::service::{self, is_image_file};
+use crate::library::{stored_archive, thumbnails};
+
+const CROPPED_COVER_QUALITY: u8 = 92;
+
+// Replace a manga's cover with a page, a crop of a page or an image file
+pub fn set_manga_cover<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    source: CoverSource,
+) -> Result<Manga, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
+    let mut manga = service::find_manga(&app, manga_id)?;
+
+    // Online manga have no folder and show their covers from the source
+    if manga.source != "local" {
+        return Err(format!("Manga with ID {} is not a local manga", manga_id));
+    }
+
+    // Read the chosen image, re-encoding it only when it was cropped. Both kinds are judged
+    // by their content, and only formats thumbnails can be made from are accepted
+    let (data, extension) = match source {
+        CoverSource::Page {
+            chapter_id,
+            page,
+            crop,
+        } => {
+            let chapter_path = manga
+                .chapters
+                .iter()
+                .find(|chapter| chapter.id == chapter_id)
+                .map(|chapter| chapter.path.clone())
+                .ok_or_else(|| format!("Chapter with ID {} not found", chapter_id))?;
+            let data = read_page(&app, &manga, &chapter_path, &page)?;
+
+            let format = cover_format(&data, &page)?;
+            match crop {
+                Some(crop) => (crop_image(&data, crop)?, "jpg".to_string()),
+                None => (data, format.extensions()[0].to_string()),
+            }
+        }
+        CoverSource::File { path } => {
+            let data = fs::read(&path).map_err(|e| format!("Failed to read cover image: {}", e))?;
+            let format = cover_format(&data, &path)?;
+            (data, format.extensions()[0].to_string())
+        }
+    };
+
+    // Linked folders belong to the user, so their covers live under app data instead
+    let (cover_dir, file_stem) = if manga.linked {
+        (app_data_dir.join("covers"), manga.id.clone())
+    } else {
+        (PathBuf::from(&manga.path), ".cover".to_string())
+    };
+    fs::create_dir_all(&cover_dir).map_err(|err| err.to_string())?;
+    remove_covers(&cover_dir, &file_stem);
+
+    let cover_path = cover_dir.join(format!("{}.{}", file_stem, extension));
+    fs::write(&cover_path, data).map_err(|e| format!("Failed to write cover image: {}", e))?;
+
+    manga.cover = cover_path
+        .to_str()
+        .ok_or("Failed to convert path to string")?
+        .to_string();
+    service::save_manga(&app, &manga)?;
+
+    // Thumbnails of the old cover are replaced right away
+    thumbnails::invalidate_thumbnails(&app_data_dir, &manga.id);
+    service::generate_cover_thumbnails(&app_data_dir, &manga);
+
+    Ok(manga)
+}
+
+// Load one page of a chapter, from the stored archive or the chapter directory
+fn read_page<R: Runtime>(
+    app: &AppHandle<R>,
+    manga: &Manga,
+    chapter_path: &str,
+    page: &str,
+) -> Result<Vec<u8>, String> {
+    // Page names come from `list_pages` and never contain a directory
+    if page.contains(['/', '\\']) || page.starts_with('.') {
+        return Err(format!("Invalid page name: {}", page));
+    }
+
+    if manga.archive.is_some() {
+        return stored_archive::read_chapter_page(app, &manga.id, chapter_path, page);
+    }
+
+    let page_path = Path::new(&manga.path).join(chapter_path).join(page);
+    fs::read(&page_path).map_err(|e| format!("Failed to read page {}: {}", page, e))
+}
+
+fn crop_image(data: &[u8], crop: CoverCrop) -> Result<Vec<u8>, String> {
+    let image =
+        image::load_from_memory(data).map_err(|e| format!("Failed to decode page image: {}", e))?;
+
+    let fits = crop.width > 0
+        && crop.height > 0
+        && crop.x.checked_add(crop.width).is_some_and(|right| right <= image.width())
+        && crop.y.checked_add(crop.height).is_some_and(|bottom| bottom <= image.height());
+    if !fits {
+        return Err(format!(
+            "Crop {}x{} at ({}, {}) is outside the {}x{} page",
+            crop.width,
+            crop.height,
+            crop.x,
+            crop.y,
+            image.width(),
+            image.height()
+        ));
+    }
+
+    let cropped = image.crop_imm(crop.x, crop.y, crop.width, crop.height);
+    let mut encoded = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut encoded, CROPPED_COVER_QUALITY)
+        .encode_image(&cropped.to_rgb8())
+        .map_err(|e| format!("Failed to encode cover image: {}", e))?;
+    Ok(encoded.into_inner())
+}
+
+// Remove earlier custom covers, which may have had another extension
+pub fn remove_covers(cover_dir: &Path, file_stem: &str) {
+    let prefix = format!("{}.", file_stem);
+    let entries = match fs::read_dir(cover_dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if file_name.starts_with(&prefix) && is_image_file(&file_name) {
+            if let Err(err) = fs::remove_file(entry.path()) {
+                println!("Failed to remove old cover {}: {}", entry.path().display(), err);
+            }
+        }
+    }
+}
+
+// Format of a cover image by its content, which may not match its name
+fn cover_format(data: &[u8], name: &str) -> Result<ImageFormat, String> {
+    match ImageFormat::sniff(data) {
+        Some(format) if format.is_decodable() => Ok(format),
+        Some(format) => Err(format!(
+            "{} images can't be used as covers: {}",
+            format.extensions()[0].to_uppercase(),
+            name
+        )),
+        None => Err(format!("Not a supported image file: {}", name)),
+    }
+}
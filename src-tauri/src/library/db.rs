@@ -0,0 +1,966 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row, Transaction};
+use rusqlite::types::{Type, Value};
+use serde::Deserialize;
+use crate::library::hashes::HashEntry;
+use crate::library::models::{
+    Category, Chapter, DuplicateMatch, HistoryEntry, Library, LibraryPage, LibraryQuery,
+    LibrarySort, Manga, ReadStatus, ReadingPosition,
+};
+
+const DATABASE_FILE: &str = "library.db";
+const LIBRARY_FILE: &str = "library.json";
+const HASH_INDEX_FILE: &str = "library-hashes.json";
+const SETTINGS_FILE: &str = "settings.json";
+
+// The frontend's stock categories, used when settings.json has none
+const DEFAULT_CATEGORIES: [(&str, &str); 5] = [
+    ("HjFCo9Zlih", "Picked Up"),
+    ("M6wEcKUjJO", "Reading"),
+    ("NOQI3t7Xuo", "On Hold"),
+    ("LmGttS3i8c", "Completed"),
+    ("ut8dw483Cj", "Dropped"),
+];
+
+// Schema changes in order; `PRAGMA user_version` records how many have been applied
+const MIGRATIONS: &[&str] = &[
+    // 1: manga, their chapters and content hashes
+    "CREATE TABLE manga (
+        id TEXT PRIMARY KEY NOT NULL,
+        title TEXT NOT NULL,
+        path TEXT NOT NULL,
+        category TEXT NOT NULL,
+        cover TEXT NOT NULL,
+        last_read TEXT,
+        created_at TEXT NOT NULL,
+        progress INTEGER NOT NULL DEFAULT 0,
+        source TEXT NOT NULL,
+        metadata TEXT NOT NULL DEFAULT '{}',
+        linked INTEGER NOT NULL DEFAULT 0,
+        source_missing INTEGER NOT NULL DEFAULT 0,
+        archive TEXT
+    );
+    CREATE INDEX manga_category ON manga (category);
+    CREATE INDEX manga_title ON manga (title COLLATE NOCASE);
+    CREATE INDEX manga_last_read ON manga (last_read);
+
+    CREATE TABLE chapters (
+        manga_id TEXT NOT NULL REFERENCES manga (id) ON DELETE CASCADE,
+        id TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        title TEXT NOT NULL,
+        number REAL,
+        volume REAL,
+        page_count INTEGER NOT NULL,
+        path TEXT NOT NULL,
+        page_order TEXT NOT NULL DEFAULT '[]',
+        PRIMARY KEY (manga_id, id)
+    );
+    CREATE INDEX chapters_position ON chapters (manga_id, position);
+
+    CREATE TABLE manga_hashes (
+        manga_id TEXT PRIMARY KEY NOT NULL REFERENCES manga (id) ON DELETE CASCADE,
+        archive_hash TEXT,
+        content_hash TEXT NOT NULL
+    );
+    CREATE INDEX manga_hashes_archive ON manga_hashes (archive_hash);
+    CREATE INDEX manga_hashes_content ON manga_hashes (content_hash);
+
+    CREATE TABLE meta (
+        key TEXT PRIMARY KEY NOT NULL,
+        value TEXT NOT NULL
+    );",
+    // 2: where each chapter was left off
+    "CREATE TABLE reading_positions (
+        manga_id TEXT NOT NULL REFERENCES manga (id) ON DELETE CASCADE,
+        chapter_id TEXT NOT NULL,
+        page INTEGER NOT NULL,
+        furthest_page INTEGER NOT NULL,
+        scroll_offset REAL NOT NULL DEFAULT 0,
+        updated_at TEXT NOT NULL,
+        PRIMARY KEY (manga_id, chapter_id)
+    );
+    CREATE INDEX reading_positions_updated ON reading_positions (manga_id, updated_at);",
+    // 3: reading sessions; not tied to the manga table so online manga and removed ones keep theirs
+    "CREATE TABLE history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        manga_id TEXT NOT NULL,
+        manga_title TEXT NOT NULL,
+        cover TEXT NOT NULL DEFAULT '',
+        chapter_id TEXT NOT NULL,
+        chapter_title TEXT NOT NULL DEFAULT '',
+        pages_read INTEGER NOT NULL,
+        started_at TEXT NOT NULL,
+        ended_at TEXT NOT NULL,
+        source TEXT NOT NULL
+    );
+    CREATE INDEX history_ended ON history (ended_at);
+    CREATE INDEX history_manga ON history (manga_id);",
+    // 4: library categories, previously kept in the frontend's settings.json
+    "CREATE TABLE categories (
+        id TEXT PRIMARY KEY NOT NULL,
+        name TEXT NOT NULL,
+        position INTEGER NOT NULL
+    );",
+    // 5: full-text index for library search, kept up to date by triggers on `manga`
+    "CREATE VIEW manga_search_source AS
+        SELECT id AS manga_id,
+            title,
+            COALESCE(json_extract(metadata, '$.series'), '') AS alternative_titles,
+            COALESCE(json_extract(metadata, '$.authors'), '') || ' '
+                || COALESCE(json_extract(metadata, '$.artists'), '') AS authors,
+            COALESCE(json_extract(metadata, '$.genres'), '') AS tags
+        FROM manga;
+    CREATE VIRTUAL TABLE manga_search USING fts5(
+        manga_id UNINDEXED,
+        title,
+        alternative_titles,
+        authors,
+        tags,
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+    INSERT INTO manga_search SELECT * FROM manga_search_source;
+
+    CREATE TRIGGER manga_search_insert AFTER INSERT ON manga BEGIN
+        INSERT INTO manga_search SELECT * FROM manga_search_source WHERE manga_id = new.id;
+    END;
+    CREATE TRIGGER manga_search_update AFTER UPDATE OF title, metadata ON manga BEGIN
+        DELETE FROM manga_search WHERE manga_id = old.id;
+        INSERT INTO manga_search SELECT * FROM manga_search_source WHERE manga_id = new.id;
+    END;
+    CREATE TRIGGER manga_search_delete AFTER DELETE ON manga BEGIN
+        DELETE FROM manga_search WHERE manga_id = old.id;
+    END;",
+    // 6: search alternative titles and tags too, now that metadata has them
+    "DROP VIEW manga_search_source;
+    CREATE VIEW manga_search_source AS
+        SELECT id AS manga_id,
+            title,
+            COALESCE(json_extract(metadata, '$.series'), '') || ' '
+                || COALESCE(json_extract(metadata, '$.alternative_titles'), '')
+                AS alternative_titles,
+            COALESCE(json_extract(metadata, '$.authors'), '') || ' '
+                || COALESCE(json_extract(metadata, '$.artists'), '') AS authors,
+            COALESCE(json_extract(metadata, '$.genres'), '') || ' '
+                || COALESCE(json_extract(metadata, '$.tags'), '') AS tags
+        FROM manga;
+    DELETE FROM manga_search;
+    INSERT INTO manga_search SELECT * FROM manga_search_source;",
+];
+
+// Set once library.json and library-hashes.json have been copied into the database
+const LEGACY_IMPORTED_KEY: &str = "legacy_files_imported";
+// Set once the categories from settings.json have been copied into the database
+const CATEGORIES_SEEDED_KEY: &str = "categories_seeded";
+
+// ComicInfo age ratings that mark a manga as NSFW, lowercased
+const ADULT_RATINGS: &str = "'adults only 18+', 'r18+', 'x18+'";
+
+// Chapters of a manga not read to their last page
+const UNREAD_CHAPTERS: &str = "(SELECT COUNT(*) FROM chapters
+    LEFT JOIN reading_positions ON reading_positions.manga_id = chapters.manga_id
+        AND reading_positions.chapter_id = chapters.id
+    WHERE chapters.manga_id = manga.id
+        AND (reading_positions.furthest_page IS NULL
+            OR reading_positions.furthest_page + 1 < chapters.page_count))";
+
+const MANGA_COLUMNS: &str = "id, title, path, category, cover, last_read, created_at, progress, \
+    source, metadata, linked, source_missing, archive";
+
+// The library store, opened once at startup and shared as managed state
+pub struct Database {
+    conn: Mutex<Connection>,
+    app_data_dir: PathBuf,
+}
+
+// Saved reading position in one chapter
+#[derive(Debug, Clone)]
+pub struct ChapterPosition {
+    pub chapter_id: String,
+    pub page: u32,
+    // Highest page ever reached, so paging back doesn't lower the progress
+    pub furthest_page: u32,
+    pub scroll_offset: f64,
+}
+
+#[derive(Deserialize)]
+struct LegacyHashIndex {
+    entries: Vec<HashEntry>,
+}
+
+#[derive(Deserialize)]
+struct LegacySettings {
+    #[serde(default)]
+    categories: Vec<Category>,
+    #[serde(default)]
+    default_category: Option<String>,
+}
+
+impl Database {
+    // Open or create library.db, bringing the schema up to date and importing the old JSON files
+    pub fn open(app_data_dir: &Path) -> Result<Database, String> {
+        fs::create_dir_all(app_data_dir).map_err(|err| err.to_string())?;
+        let mut conn = Connection::open(app_data_dir.join(DATABASE_FILE))
+            .map_err(|e| format!("Failed to open library database: {}", e))?;
+        conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;")
+            .map_err(|err| err.to_string())?;
+        migrate(&mut conn)?;
+
+        let database = Database {
+            conn: Mutex::new(conn),
+            app_data_dir: app_data_dir.to_path_buf(),
+        };
+        database.import_legacy_files()?;
+        database.seed_categories()?;
+        Ok(database)
+    }
+
+    // Every manga in the order they were added
+    pub fn library(&self) -> Result<Library, String> {
+        let conn = self.lock();
+        load_library(&conn)
+    }
+
+    // One page of the manga matching a search and filters, with the number of matches
+    pub fn query_library(&self, query: &LibraryQuery) -> Result<LibraryPage, String> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+
+        if let Some(search) = query.search.as_deref().and_then(search_expression) {
+            conditions.push("id IN (SELECT manga_id FROM manga_search WHERE manga_search MATCH ?)");
+            values.push(Value::Text(search));
+        }
+        if let Some(category) = &query.category {
+            conditions.push("category = ?");
+            values.push(Value::Text(category.clone()));
+        }
+        if let Some(source) = &query.source {
+            conditions.push("source = ?");
+            values.push(Value::Text(source.clone()));
+        }
+        // Progress is only ever raised by reading, so a manga at 0 never read is unread
+        match query.read_status {
+            Some(ReadStatus::Unread) => conditions.push("progress = 0 AND last_read IS NULL"),
+            Some(ReadStatus::Reading) => conditions
+                .push("progress < 100 AND NOT (progress = 0 AND last_read IS NULL)"),
+            Some(ReadStatus::Completed) => conditions.push("progress >= 100"),
+            None => {}
+        }
+        let adult = format!(
+            "COALESCE(lower(json_extract(metadata, '$.age_rating')) IN ({}), 0) = ?",
+            ADULT_RATINGS
+        );
+        if let Some(nsfw) = query.nsfw {
+            conditions.push(&adult);
+            values.push(Value::Integer(nsfw as i64));
+        }
+
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let descending = query.descending.unwrap_or(query.sort != LibrarySort::Title);
+        let direction = if descending { "DESC" } else { "ASC" };
+        let order = match query.sort {
+            LibrarySort::Title => format!("title COLLATE NOCASE {}", direction),
+            // Never read goes last either way
+            LibrarySort::LastRead => format!("last_read IS NULL, last_read {}", direction),
+            // Rows keep their rowid when updated, so it is the order manga were added in
+            LibrarySort::DateAdded => format!("rowid {}", direction),
+            LibrarySort::Unread => format!("{} {}", UNREAD_CHAPTERS, direction),
+        };
+
+        let conn = self.lock();
+        let total = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM manga {}", filter),
+                params_from_iter(&values),
+                |row| row.get(0),
+            )
+            .map_err(|err| err.to_string())?;
+
+        // A negative limit means no limit to SQLite
+        values.push(Value::Integer(query.limit.map_or(-1, i64::from)));
+        values.push(Value::Integer(query.offset.into()));
+        let mut manga = conn
+            .prepare(&format!(
+                "SELECT {} FROM manga {} ORDER BY {}, title COLLATE NOCASE, rowid LIMIT ? OFFSET ?",
+                MANGA_COLUMNS, filter, order
+            ))
+            .and_then(|mut statement| {
+                listed_manga(statement.query_map(params_from_iter(&values), listed_manga_row)?)
+            })
+            .map_err(|err| err.to_string())?;
+
+        for manga in &mut manga {
+            manga.chapters = chapters(&conn, &manga.id)?;
+        }
+        Ok(LibraryPage { manga, total })
+    }
+
+    pub fn manga(&self, manga_id: &str) -> Result<Option<Manga>, String> {
+        let conn = self.lock();
+        let manga = conn
+            .query_row(
+                &format!("SELECT {} FROM manga WHERE id = ?1", MANGA_COLUMNS),
+                [manga_id],
+                manga_from_row,
+            )
+            .optional()
+            .map_err(|err| err.to_string())?;
+
+        match manga {
+            Some(mut manga) => {
+                manga.chapters = chapters(&conn, manga_id)?;
+                Ok(Some(manga))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Insert or update a manga together with its chapters
+    pub fn save_manga(&self, manga: &Manga) -> Result<(), String> {
+        let mut conn = self.lock();
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
+        upsert_manga(&tx, manga)?;
+        tx.commit().map_err(|err| err.to_string())
+    }
+
+    // Register a new import and its hashes in one transaction, so neither exists without the other
+    pub fn add_manga(&self, manga: &Manga, hashes: &HashEntry) -> Result<(), String> {
+        let mut conn = self.lock();
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
+        upsert_manga(&tx, manga)?;
+        insert_hashes(&tx, hashes)?;
+        tx.commit().map_err(|err| err.to_string())
+    }
+
+    // Remove a manga with its chapters and hashes; false when there was no such manga
+    pub fn remove_manga(&self, manga_id: &str) -> Result<bool, String> {
+        let removed = self
+            .lock()
+            .execute("DELETE FROM manga WHERE id = ?1", [manga_id])
+            .map_err(|e| format!("Failed to remove manga {}: {}", manga_id, e))?;
+        Ok(removed > 0)
+    }
+
+    // Store where a chapter was left off and when, keeping the furthest page reached
+    pub fn save_position(
+        &self,
+        manga_id: &str,
+        position: &ReadingPosition,
+        read_at: &str,
+    ) -> Result<(), String> {
+        self.lock()
+            .execute(
+                "INSERT INTO reading_positions
+                    (manga_id, chapter_id, page, furthest_page, scroll_offset, updated_at)
+                 VALUES (?1, ?2, ?3, ?3, ?4, ?5)
+                 ON CONFLICT (manga_id, chapter_id) DO UPDATE SET
+                    page = excluded.page,
+                    furthest_page = MAX(furthest_page, excluded.page),
+                    scroll_offset = excluded.scroll_offset,
+                    updated_at = excluded.updated_at",
+                params![
+                    manga_id,
+                    position.chapter_id,
+                    position.page,
+                    position.scroll_offset,
+                    read_at,
+                ],
+            )
+            .map_err(|e| format!("Failed to save reading position: {}", e))?;
+        Ok(())
+    }
+
+    // Saved positions of a manga, most recently read first
+    pub fn positions(&self, manga_id: &str) -> Result<Vec<ChapterPosition>, String> {
+        self.lock()
+            .prepare(
+                "SELECT chapter_id, page, furthest_page, scroll_offset FROM reading_positions
+                 WHERE manga_id = ?1 ORDER BY updated_at DESC",
+            )
+            .and_then(|mut statement| {
+                statement
+                    .query_map([manga_id], |row| {
+                        Ok(ChapterPosition {
+                            chapter_id: row.get(0)?,
+                            page: row.get(1)?,
+                            furthest_page: row.get(2)?,
+                            scroll_offset: row.get(3)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<ChapterPosition>, _>>()
+            })
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn set_progress(
+        &self,
+        manga_id: &str,
+        progress: u32,
+        last_read: &str,
+    ) -> Result<(), String> {
+        self.lock()
+            .execute(
+                "UPDATE manga SET progress = ?2, last_read = ?3 WHERE id = ?1",
+                params![manga_id, progress, last_read],
+            )
+            .map_err(|e| format!("Failed to save reading progress: {}", e))?;
+        Ok(())
+    }
+
+    // Log a reading session, returning it with its new ID
+    pub fn add_history(&self, entry: &HistoryEntry) -> Result<HistoryEntry, String> {
+        let conn = self.lock();
+        conn.execute(
+            "INSERT INTO history (manga_id, manga_title, cover, chapter_id, chapter_title,
+                pages_read, started_at, ended_at, source)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                entry.manga_id,
+                entry.manga_title,
+                entry.cover,
+                entry.chapter_id,
+                entry.chapter_title,
+                entry.pages_read,
+                entry.started_at,
+                entry.ended_at,
+                entry.source,
+            ],
+        )
+        .map_err(|e| format!("Failed to record history: {}", e))?;
+
+        Ok(HistoryEntry {
+            id: conn.last_insert_rowid(),
+            ..entry.clone()
+        })
+    }
+
+    // Reading sessions, most recently finished first
+    pub fn history(&self, limit: u32, offset: u32) -> Result<Vec<HistoryEntry>, String> {
+        self.lock()
+            .prepare(
+                "SELECT id, manga_id, manga_title, cover, chapter_id, chapter_title, pages_read,
+                    started_at, ended_at, source
+                 FROM history ORDER BY ended_at DESC, id DESC LIMIT ?1 OFFSET ?2",
+            )
+            .and_then(|mut statement| {
+                statement
+                    .query_map([limit, offset], |row| {
+                        Ok(HistoryEntry {
+                            id: row.get(0)?,
+                            manga_id: row.get(1)?,
+                            manga_title: row.get(2)?,
+                            cover: row.get(3)?,
+                            chapter_id: row.get(4)?,
+                            chapter_title: row.get(5)?,
+                            pages_read: row.get(6)?,
+                            started_at: row.get(7)?,
+                            ended_at: row.get(8)?,
+                            source: row.get(9)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<HistoryEntry>, _>>()
+            })
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn delete_history_entry(&self, id: i64) -> Result<bool, String> {
+        let removed = self
+            .lock()
+            .execute("DELETE FROM history WHERE id = ?1", [id])
+            .map_err(|e| format!("Failed to delete history entry: {}", e))?;
+        Ok(removed > 0)
+    }
+
+    // Delete sessions that ended within [from, to); a missing bound leaves that side open
+    pub fn delete_history_range(
+        &self,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Result<usize, String> {
+        self.lock()
+            .execute(
+                "DELETE FROM history
+                 WHERE (?1 IS NULL OR ended_at >= ?1) AND (?2 IS NULL OR ended_at < ?2)",
+                params![from, to],
+            )
+            .map_err(|e| format!("Failed to delete history: {}", e))
+    }
+
+    // Categories in display order, with how many manga each holds
+    pub fn categories(&self) -> Result<Vec<Category>, String> {
+        self.lock()
+            .prepare(
+                "SELECT categories.id, categories.name, COUNT(manga.id) FROM categories
+                 LEFT JOIN manga ON manga.category = categories.id
+                 GROUP BY categories.id ORDER BY categories.position",
+            )
+            .and_then(|mut statement| {
+                statement
+                    .query_map([], |row| {
+                        Ok(Category {
+                            id: row.get(0)?,
+                            name: row.get(1)?,
+                            manga_count: row.get(2)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<Category>, _>>()
+            })
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn category_exists(&self, category_id: &str) -> Result<bool, String> {
+        self.lock()
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM categories WHERE id = ?1)",
+                [category_id],
+                |row| row.get(0),
+            )
+            .map_err(|err| err.to_string())
+    }
+
+    // Append a category after the existing ones
+    pub fn add_category(&self, category: &Category) -> Result<(), String> {
+        self.lock()
+            .execute(
+                "INSERT INTO categories (id, name, position)
+                 VALUES (?1, ?2, (SELECT COALESCE(MAX(position), -1) + 1 FROM categories))",
+                [&category.id, &category.name],
+            )
+            .map_err(|e| format!("Failed to create category: {}", e))?;
+        Ok(())
+    }
+
+    pub fn rename_category(&self, category_id: &str, name: &str) -> Result<bool, String> {
+        let renamed = self
+            .lock()
+            .execute("UPDATE categories SET name = ?2 WHERE id = ?1", [category_id, name])
+            .map_err(|e| format!("Failed to rename category: {}", e))?;
+        Ok(renamed > 0)
+    }
+
+    // Give every category its index in `category_ids` as position
+    pub fn reorder_categories(&self, category_ids: &[String]) -> Result<(), String> {
+        let mut conn = self.lock();
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
+        for (position, category_id) in category_ids.iter().enumerate() {
+            tx.execute(
+                "UPDATE categories SET position = ?2 WHERE id = ?1",
+                params![category_id, position],
+            )
+            .map_err(|e| format!("Failed to reorder categories: {}", e))?;
+        }
+        tx.commit().map_err(|err| err.to_string())
+    }
+
+    // Delete a category, first moving its manga to `reassign_to`
+    pub fn delete_category(
+        &self,
+        category_id: &str,
+        reassign_to: Option<&str>,
+    ) -> Result<(), String> {
+        let mut conn = self.lock();
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
+        if let Some(reassign_to) = reassign_to {
+            tx.execute(
+                "UPDATE manga SET category = ?2 WHERE category = ?1",
+                [category_id, reassign_to],
+            )
+            .map_err(|e| format!("Failed to move manga to another category: {}", e))?;
+        }
+
+        // Checked inside the transaction so an import can't slip into the category meanwhile
+        let remaining: u32 = tx
+            .query_row("SELECT COUNT(*) FROM manga WHERE category = ?1", [category_id], |row| {
+                row.get(0)
+            })
+            .map_err(|err| err.to_string())?;
+        if remaining > 0 {
+            return Err(format!(
+                "Category still holds {} manga; choose a category to move them to",
+                remaining
+            ));
+        }
+
+        tx.execute("DELETE FROM categories WHERE id = ?1", [category_id])
+            .map_err(|e| format!("Failed to delete category: {}", e))?;
+        tx.commit().map_err(|err| err.to_string())
+    }
+
+    // Manga imported from the same file
+    pub fn find_by_archive_hash(&self, hash: &str) -> Result<Option<DuplicateMatch>, String> {
+        self.find_duplicate("archive_hash", hash)
+    }
+
+    // Manga with the same pages, however they were packaged
+    pub fn find_by_content_hash(&self, hash: &str) -> Result<Option<DuplicateMatch>, String> {
+        self.find_duplicate("content_hash", hash)
+    }
+
+    fn find_duplicate(&self, column: &str, hash: &str) -> Result<Option<DuplicateMatch>, String> {
+        let conn = self.lock();
+        conn.query_row(
+            &format!(
+                "SELECT manga.id, manga.title FROM manga_hashes
+                 JOIN manga ON manga.id = manga_hashes.manga_id
+                 WHERE manga_hashes.{} = ?1 ORDER BY manga.rowid LIMIT 1",
+                column
+            ),
+            [hash],
+            |row| {
+                Ok(DuplicateMatch {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|err| err.to_string())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap()
+    }
+
+    // Copy library.json and library-hashes.json over the first time the database is opened
+    fn import_legacy_files(&self) -> Result<(), String> {
+        let mut conn = self.lock();
+        if meta_value(&conn, LEGACY_IMPORTED_KEY)?.is_some() {
+            return Ok(());
+        }
+
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
+        for manga in read_library_file(&self.app_data_dir.join(LIBRARY_FILE)).unwrap_or_default() {
+            upsert_manga(&tx, &manga)?;
+        }
+
+        // Hashes of manga that are no longer in the library are dropped
+        let hash_index: Option<LegacyHashIndex> =
+            fs::read_to_string(self.app_data_dir.join(HASH_INDEX_FILE))
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok());
+        for entry in hash_index.map(|index| index.entries).unwrap_or_default() {
+            let exists: bool = tx
+                .query_row(
+                    "SELECT EXISTS (SELECT 1 FROM manga WHERE id = ?1)",
+                    [&entry.manga_id],
+                    |row| row.get(0),
+                )
+                .map_err(|err| err.to_string())?;
+            if exists {
+                insert_hashes(&tx, &entry)?;
+            }
+        }
+
+        // The old files are left in place as a backup
+        set_meta_value(&tx, LEGACY_IMPORTED_KEY, "1")?;
+        tx.commit().map_err(|err| err.to_string())
+    }
+
+    // Copy the categories from settings.json the first time, and move manga whose category
+    // no longer exists into the default one
+    fn seed_categories(&self) -> Result<(), String> {
+        let mut conn = self.lock();
+        if meta_value(&conn, CATEGORIES_SEEDED_KEY)?.is_some() {
+            return Ok(());
+        }
+
+        let settings: Option<LegacySettings> =
+            fs::read_to_string(self.app_data_dir.join(SETTINGS_FILE))
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok());
+        let (mut categories, default_category) = settings
+            .map(|settings| (settings.categories, settings.default_category))
+            .unwrap_or_default();
+        if categories.is_empty() {
+            categories = DEFAULT_CATEGORIES
+                .iter()
+                .map(|(id, name)| Category {
+                    id: id.to_string(),
+                    name: name.to_string(),
+                    manga_count: 0,
+                })
+                .collect();
+        }
+
+        let fallback = default_category
+            .filter(|id| categories.iter().any(|category| category.id == *id))
+            .unwrap_or_else(|| categories[0].id.clone());
+
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
+        for (position, category) in categories.iter().enumerate() {
+            tx.execute(
+                "INSERT OR IGNORE INTO categories (id, name, position) VALUES (?1, ?2, ?3)",
+                params![category.id, category.name, position],
+            )
+            .map_err(|e| format!("Failed to create category: {}", e))?;
+        }
+        tx.execute(
+            "UPDATE manga SET category = ?1
+             WHERE category NOT IN (SELECT id FROM categories)",
+            [&fallback],
+        )
+        .map_err(|err| err.to_string())?;
+
+        set_meta_value(&tx, CATEGORIES_SEEDED_KEY, "1")?;
+        tx.commit().map_err(|err| err.to_string())
+    }
+}
+
+// Turn what the user typed into an FTS5 query matching every word as a prefix, with quotes
+// keeping FTS5 syntax out of it
+fn search_expression(search: &str) -> Option<String> {
+    let terms: Vec<String> = search
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+// Every manga with its chapters, in the order they were added
+fn load_library(conn: &Connection) -> Result<Library, String> {
+    let mut manga = conn
+        .prepare(&format!("SELECT {} FROM manga ORDER BY rowid", MANGA_COLUMNS))
+        .and_then(|mut statement| listed_manga(statement.query_map([], listed_manga_row)?))
+        .map_err(|err| err.to_string())?;
+
+    // One query for all chapters instead of one per manga
+    let mut chapters = all_chapters(conn)?;
+    for manga in &mut manga {
+        manga.chapters = chapters.remove(&manga.id).unwrap_or_default();
+    }
+    Ok(Library { manga })
+}
+
+// Apply the migrations the database hasn't seen yet, each in its own transaction
+fn migrate(conn: &mut Connection) -> Result<(), String> {
+    let version: usize = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|err| err.to_string())?;
+    if version > MIGRATIONS.len() {
+        return Err(format!(
+            "Library database version {} is newer than this app supports ({})",
+            version,
+            MIGRATIONS.len()
+        ));
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
+        tx.execute_batch(migration)
+            .and_then(|_| tx.pragma_update(None, "user_version", index + 1))
+            .map_err(|e| {
+                format!("Failed to migrate library database to version {}: {}", index + 1, e)
+            })?;
+        tx.commit().map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+// Entries of a library.json; ones that don't parse are left out rather than losing the whole file
+fn read_library_file(path: &Path) -> Option<Vec<Manga>> {
+    let content = fs::read_to_string(path).ok()?;
+    let library: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let entries = library.get("manga")?.as_array()?;
+
+    Some(
+        entries
+            .iter()
+            .filter_map(|entry| match serde_json::from_value(entry.clone()) {
+                Ok(manga) => Some(manga),
+                Err(err) => {
+                    println!("Skipping unreadable library entry: {}", err);
+                    None
+                }
+            })
+            .collect(),
+    )
+}
+
+// An update keeps the row (and its hashes) in place, where INSERT OR REPLACE would delete it
+fn upsert_manga(tx: &Transaction, manga: &Manga) -> Result<(), String> {
+    let metadata = serde_json::to_string(&manga.metadata).map_err(|err| err.to_string())?;
+    tx.execute(
+        &format!(
+            "INSERT INTO manga ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+             ON CONFLICT (id) DO UPDATE SET
+                title = excluded.title, path = excluded.path, category = excluded.category,
+                cover = excluded.cover, last_read = excluded.last_read,
+                created_at = excluded.created_at, progress = excluded.progress,
+                source = excluded.source, metadata = excluded.metadata, linked = excluded.linked,
+                source_missing = excluded.source_missing, archive = excluded.archive",
+            MANGA_COLUMNS
+        ),
+        params![
+            manga.id,
+            manga.title,
+            manga.path,
+            manga.category,
+            manga.cover,
+            manga.last_read,
+            manga.created_at,
+            manga.progress,
+            manga.source,
+            metadata,
+            manga.linked,
+            manga.source_missing,
+            manga.archive,
+        ],
+    )
+    .map_err(|e| format!("Failed to save manga {}: {}", manga.id, e))?;
+
+    tx.execute("DELETE FROM chapters WHERE manga_id = ?1", [&manga.id])
+        .map_err(|err| err.to_string())?;
+    for (position, chapter) in manga.chapters.iter().enumerate() {
+        let page_order = serde_json::to_string(&chapter.page_order).map_err(|err| err.to_string())?;
+        tx.execute(
+            "INSERT INTO chapters
+                (manga_id, id, position, title, number, volume, page_count, path, page_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                manga.id,
+                chapter.id,
+                position,
+                chapter.title,
+                chapter.number,
+                chapter.volume,
+                chapter.page_count,
+                chapter.path,
+                page_order,
+            ],
+        )
+        .map_err(|e| format!("Failed to save chapter {}: {}", chapter.id, e))?;
+    }
+    Ok(())
+}
+
+fn insert_hashes(tx: &Transaction, hashes: &HashEntry) -> Result<(), String> {
+    tx.execute(
+        "INSERT OR REPLACE INTO manga_hashes (manga_id, archive_hash, content_hash)
+         VALUES (?1, ?2, ?3)",
+        params![hashes.manga_id, hashes.archive_hash, hashes.content_hash],
+    )
+    .map_err(|e| format!("Failed to record hashes: {}", e))?;
+    Ok(())
+}
+
+fn manga_from_row(row: &Row) -> rusqlite::Result<Manga> {
+    // Metadata is stored as JSON; fields added later fall back to their defaults, but JSON
+    // that doesn't parse is an error rather than being silently replaced on the next save
+    let metadata: String = row.get(9)?;
+    let metadata = serde_json::from_str(&metadata).map_err(|err| {
+        rusqlite::Error::FromSqlConversionFailure(9, Type::Text, Box::new(err))
+    })?;
+    Ok(Manga {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        path: row.get(2)?,
+        category: row.get(3)?,
+        cover: row.get(4)?,
+        last_read: row.get(5)?,
+        created_at: row.get(6)?,
+        progress: row.get(7)?,
+        source: row.get(8)?,
+        chapters: Vec::new(),
+        metadata,
+        linked: row.get(10)?,
+        source_missing: row.get(11)?,
+        archive: row.get(12)?,
+    })
+}
+
+// A manga row for a listing, with its id kept apart so a row that can't be read is still named
+fn listed_manga_row(row: &Row) -> rusqlite::Result<(String, rusqlite::Result<Manga>)> {
+    Ok((row.get(0)?, manga_from_row(row)))
+}
+
+// One entry with broken metadata shouldn't hide the whole library, so listings leave it out;
+// opening or saving it still reports the error
+fn listed_manga(
+    rows: impl Iterator<Item = rusqlite::Result<(String, rusqlite::Result<Manga>)>>,
+) -> rusqlite::Result<Vec<Manga>> {
+    let mut manga = Vec::new();
+    for row in rows {
+        match row? {
+            (_, Ok(row)) => manga.push(row),
+            (manga_id, Err(rusqlite::Error::FromSqlConversionFailure(9, _, err))) => {
+                println!("Skipping manga {} with unreadable metadata: {}", manga_id, err);
+            }
+            (_, Err(err)) => return Err(err),
+        }
+    }
+    Ok(manga)
+}
+
+fn chapter_from_row(row: &Row) -> rusqlite::Result<Chapter> {
+    let page_order: String = row.get(6)?;
+    Ok(Chapter {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        number: row.get(2)?,
+        volume: row.get(3)?,
+        page_count: row.get(4)?,
+        path: row.get(5)?,
+        page_order: serde_json::from_str(&page_order).unwrap_or_default(),
+    })
+}
+
+const CHAPTER_COLUMNS: &str = "id, title, number, volume, page_count, path, page_order";
+
+fn chapters(conn: &Connection, manga_id: &str) -> Result<Vec<Chapter>, String> {
+    conn.prepare(&format!(
+        "SELECT {} FROM chapters WHERE manga_id = ?1 ORDER BY position",
+        CHAPTER_COLUMNS
+    ))
+    .and_then(|mut statement| {
+        statement
+            .query_map([manga_id], chapter_from_row)?
+            .collect::<Result<Vec<Chapter>, _>>()
+    })
+    .map_err(|err| err.to_string())
+}
+
+// Chapters of every manga, grouped by manga id
+fn all_chapters(conn: &Connection) -> Result<HashMap<String, Vec<Chapter>>, String> {
+    let mut statement = conn
+        .prepare(&format!(
+            "SELECT {}, manga_id FROM chapters ORDER BY manga_id, position",
+            CHAPTER_COLUMNS
+        ))
+        .map_err(|err| err.to_string())?;
+    let rows = statement
+        .query_map([], |row| Ok((row.get::<_, String>(7)?, chapter_from_row(row)?)))
+        .map_err(|err| err.to_string())?;
+
+    let mut chapters: HashMap<String, Vec<Chapter>> = HashMap::new();
+    for row in rows {
+        let (manga_id, chapter) = row.map_err(|err| err.to_string())?;
+        chapters.entry(manga_id).or_default().push(chapter);
+    }
+    Ok(chapters)
+}
+
+fn meta_value(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+        .map_err(|err| err.to_string())
+}
+
+fn set_meta_value(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        [key, value],
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(())
+}
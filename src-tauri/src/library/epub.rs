@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use roxmltree::{Document, ParsingOptions};
+use zip::ZipArchive;
+use crate::library::images::ImageFormat;
+use crate::library::models::MangaMetadata;
+use crate::library::progress::ImportTracker;
+use crate::library::util::{percent_decode, read_entry};
+
+const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+
+// Package metadata read from the EPUB's OPF file
+pub struct EpubPackage {
+    pub title: Option<String>,
+    pub metadata: MangaMetadata,
+}
+
+// Extract the page images of a fixed-layout EPUB in spine order
+pub fn extract_epub_pages(
+    epub_path: &Path,
+    destination: &Path,
+    tracker: &mut ImportTracker,
+) -> Result<EpubPackage, String> {
+    let file = fs::File::open(epub_path).map_err(|e| format!("Failed to open EPUB file: {}", e))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| format!("Failed to read EPUB as ZIP archive: {}", e))?;
+
+    // The container file points at the OPF package document
+    let container = read_entry_text(&mut archive, "META-INF/container.xml")?;
+    let container = parse_xml(&container)?;
+    let opf_path = container
+        .descendants()
+        .find(|node| node.tag_name().name() == "rootfile")
+        .and_then(|node| node.attribute("full-path"))
+        .ok_or("EPUB container does not reference a package document")?
+        .to_string();
+
+    let opf = read_entry_text(&mut archive, &opf_path)?;
+    let opf = parse_xml(&opf)?;
+    let opf_dir = parent_dir(&opf_path);
+
+    // Title, authors, subjects and reading direction come from the package metadata
+    let title = find_text(&opf, "title");
+    let all_text = |tag: &str| {
+        opf.descendants()
+            .filter(|node| node.tag_name().name() == tag)
+            .filter_map(|node| node.text())
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect()
+    };
+    let authors = all_text("creator");
+
+    let spine = opf
+        .descendants()
+        .find(|node| node.tag_name().name() == "spine")
+        .ok_or("EPUB package has no spine")?;
+    let reading_direction = match spine.attribute("page-progression-direction") {
+        Some("rtl") => Some("right-to-left".to_string()),
+        Some("ltr") => Some("left-to-right".to_string()),
+        _ => None,
+    };
+
+    // Map manifest ids to (archive path, media type)
+    let manifest: HashMap<&str, (String, &str)> = opf
+        .descendants()
+        .filter(|node| node.tag_name().name() == "item")
+        .filter_map(|node| {
+            let id = node.attribute("id")?;
+            let href = node.attribute("href")?;
+            let media_type = node.attribute("media-type").unwrap_or_default();
+            Some((id, (resolve_href(&opf_dir, href), media_type)))
+        })
+        .collect();
+
+    // Resolve every spine item to the image it displays
+    let mut page_number = 0;
+    for itemref in spine.children().filter(|node| node.tag_name().name() == "itemref") {
+        tracker.check_cancelled()?;
+
+        let (item_path, media_type) = match itemref
+            .attribute("idref")
+            .and_then(|idref| manifest.get(idref))
+        {
+            Some(item) => item,
+            None => continue,
+        };
+
+        let image_path = if media_type.starts_with("image/") {
+            Some(item_path.clone())
+        } else {
+            let page = read_entry_text(&mut archive, item_path)?;
+            find_page_image(&page, &parent_dir(item_path))?
+        };
+
+        // Text-only pages (e.g. a colophon) have no image to keep
+        let image_path = match image_path {
+            Some(image_path) => image_path,
+            None => {
+                tracker.file_skipped(item_path, "no page image");
+                continue;
+            }
+        };
+
+        // The manifest's media type isn't trusted; the page is named after its content
+        let data = read_entry(&mut archive, &image_path)?;
+        let format = match ImageFormat::sniff(&data) {
+            Some(format) => format,
+            None => {
+                tracker.file_skipped(&image_path, "not a recognised image format");
+                continue;
+            }
+        };
+
+        page_number += 1;
+        let extension = format.extensions()[0];
+        let dest_path = destination.join(format!("{:04}.{}", page_number, extension));
+        fs::write(&dest_path, &data).map_err(|e| format!("Failed to write file data: {}", e))?;
+        tracker.file_written(&image_path, data.len() as u64);
+    }
+
+    if page_number == 0 {
+        return Err("EPUB contains no page images; only fixed-layout EPUBs are supported".to_string());
+    }
+
+    Ok(EpubPackage {
+        title,
+        metadata: MangaMetadata {
+            authors,
+            reading_direction,
+            description: find_text(&opf, "description"),
+            tags: all_text("subject"),
+            language: find_text(&opf, "language"),
+            ..MangaMetadata::default()
+        },
+    })
+}
+
+// Find the image shown by an XHTML page, either an <img> or an SVG <image>
+fn find_page_image(page: &str, page_dir: &str) -> Result<Option<String>, String> {
+    let document = parse_xml(page)?;
+    let href = document.descendants().find_map(|node| match node.tag_name().name() {
+        "img" => node.attribute("src"),
+        "image" => node.attribute((XLINK_NS, "href")).or_else(|| node.attribute("href")),
+        _ => None,
+    });
+
+    Ok(href.map(|href| resolve_href(page_dir, href)))
+}
+
+fn find_text(document: &Document, tag: &str) -> Option<String> {
+    document
+        .descendants()
+        .find(|node| node.tag_name().name() == tag)
+        .and_then(|node| node.text())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+fn parse_xml(text: &str) -> Result<Document<'_>, String> {
+    // XHTML pages commonly carry a DOCTYPE, which roxmltree rejects by default
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    Document::parse_with_options(text, options).map_err(|e| format!("Invalid EPUB XML: {}", e))
+}
+
+fn read_entry_text(archive: &mut ZipArchive<fs::File>, name: &str) -> Result<String, String> {
+    let data = read_entry(archive, name)?;
+    String::from_utf8(data).map_err(|e| format!("Invalid text in {}: {}", name, e))
+}
+
+// Directory part of an archive path, with a trailing slash when not empty
+fn parent_dir(path: &str) -> String {
+    match path.rfind('/') {
+        Some(index) => path[..=index].to_string(),
+        None => String::new(),
+    }
+}
+
+// Resolve a (percent-encoded) href against a directory inside the archive
+fn resolve_href(base_dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let mut segments: Vec<String> = Vec::new();
+
+    for segment in format!("{}{}", base_dir, percent_decode(href)).split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment.to_string()),
+        }
+    }
+
+    segments.join("/")
+}
@@ -0,0 +1,101 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use crate::library::images;
+use crate::library::progress::ImportTracker;
+use crate::library::service::natural_cmp;
+use crate::library::stored_archive::StoredArchive;
+
+const HASH_CHUNK_SIZE: usize = 1024 * 1024;
+
+// Hashes recorded for a manga when it is imported, kept in the `manga_hashes` table
+#[derive(Deserialize, Debug, Clone)]
+pub struct HashEntry {
+    pub manga_id: String,
+    // Hash of the imported file itself, for archive, PDF and EPUB imports
+    #[serde(default)]
+    pub archive_hash: Option<String>,
+    // Hash of the page images in reading order, whatever they were imported from
+    pub content_hash: String,
+}
+
+// SHA-256 of a single file, streamed so large archives aren't loaded into memory. Large files
+// take a while, so cancelling the import is checked between chunks
+pub fn hash_file(path: &Path, tracker: &ImportTracker) -> Result<String, String> {
+    let mut file =
+        fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; HASH_CHUNK_SIZE];
+    loop {
+        tracker.check_cancelled()?;
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to hash {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// SHA-256 over the page images below a directory in natural path order. Only the page
+// bytes are hashed, so the same pages match across folders, archives and PDFs. There is no
+// hash without pages, since every empty import would hash the same
+pub fn hash_pages(dir: &Path, tracker: &ImportTracker) -> Result<Option<String>, String> {
+    let mut pages = Vec::new();
+    collect_pages(dir, dir, &mut pages)?;
+    if pages.is_empty() {
+        return Ok(None);
+    }
+    pages.sort_by(|(a, _), (b, _)| natural_cmp(a, b));
+
+    let mut hasher = Sha256::new();
+    for (_, path) in &pages {
+        hasher.update(hash_file(path, tracker)?.as_bytes());
+    }
+    Ok(Some(format!("{:x}", hasher.finalize())))
+}
+
+// Same as `hash_pages`, for the pages of an archive read in place
+pub fn hash_archive_pages(
+    archive: &mut StoredArchive,
+    tracker: &ImportTracker,
+) -> Result<Option<String>, String> {
+    if archive.page_entries().is_empty() {
+        return Ok(None);
+    }
+
+    let mut hasher = Sha256::new();
+    for page in archive.page_entries().to_vec() {
+        tracker.check_cancelled()?;
+        let data = archive.read_entry(&page)?;
+        hasher.update(format!("{:x}", Sha256::digest(&data)).as_bytes());
+    }
+    Ok(Some(format!("{:x}", hasher.finalize())))
+}
+
+// Collect (relative path, absolute path) pairs of the files that are images by content,
+// skipping hidden files such as stored covers
+fn collect_pages(root: &Path, dir: &Path, pages: &mut Vec<(String, PathBuf)>) -> Result<(), String> {
+    for entry in fs::read_dir(dir).map_err(|err| err.to_string())? {
+        let path = entry.map_err(|err| err.to_string())?.path();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if file_name.starts_with('.') {
+            continue;
+        }
+
+        if path.is_dir() {
+            collect_pages(root, &path, pages)?;
+        } else if images::sniff_file(&path).map_err(|err| err.to_string())?.is_some() {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            pages.push((relative.to_string_lossy().to_string(), path.clone()));
+        }
+    }
+    Ok(())
+}
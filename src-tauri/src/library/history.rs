@@ -0,0 +1,94 @@
+use tauri::{AppHandle, Manager, Runtime};
+use std::sync::atomic::{AtomicBool, Ordering};
+use chrono::{DateTime, SecondsFormat, Utc};
+use crate::library::db::Database;
+use crate::library::models::HistoryEntry;
+
+const DEFAULT_HISTORY_LIMIT: u32 = 50;
+
+// While enabled, reading sessions are not written to the history. It only lasts until the
+// app is closed, so a forgotten incognito session doesn't silently stop the history for good
+#[derive(Default)]
+pub struct IncognitoMode {
+    enabled: AtomicBool,
+}
+
+impl IncognitoMode {
+    pub fn set(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+}
+
+// Log a finished reading session; returns nothing when incognito mode dropped it
+pub fn record_history<R: Runtime>(
+    app: AppHandle<R>,
+    entry: HistoryEntry,
+) -> Result<Option<HistoryEntry>, String> {
+    if app.state::<IncognitoMode>().is_enabled() {
+        return Ok(None);
+    }
+    if entry.manga_id.is_empty() {
+        return Err("History entry has no manga ID".to_string());
+    }
+
+    let started_at = normalize_timestamp(&entry.started_at)?;
+    let ended_at = normalize_timestamp(&entry.ended_at)?;
+    if ended_at < started_at {
+        return Err("History entry ends before it starts".to_string());
+    }
+
+    let entry = HistoryEntry {
+        started_at,
+        ended_at,
+        ..entry
+    };
+    app.state::<Database>().add_history(&entry).map(Some)
+}
+
+// Recent sessions for the history screen, newest first
+pub fn get_history<R: Runtime>(
+    app: AppHandle<R>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Vec<HistoryEntry>, String> {
+    app.state::<Database>()
+        .history(limit.unwrap_or(DEFAULT_HISTORY_LIMIT), offset.unwrap_or_default())
+}
+
+pub fn delete_history_entry<R: Runtime>(app: AppHandle<R>, id: i64) -> Result<(), String> {
+    if !app.state::<Database>().delete_history_entry(id)? {
+        return Err(format!("History entry {} not found", id));
+    }
+    Ok(())
+}
+
+// Delete sessions that ended between two times, e.g. "today"; without bounds this clears it all
+pub fn delete_history_range<R: Runtime>(
+    app: AppHandle<R>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<usize, String> {
+    let from = from.as_deref().map(normalize_timestamp).transpose()?;
+    let to = to.as_deref().map(normalize_timestamp).transpose()?;
+    app.state::<Database>()
+        .delete_history_range(from.as_deref(), to.as_deref())
+}
+
+pub fn set_incognito<R: Runtime>(app: AppHandle<R>, enabled: bool) {
+    app.state::<IncognitoMode>().set(enabled);
+}
+
+pub fn get_incognito<R: Runtime>(app: AppHandle<R>) -> bool {
+    app.state::<IncognitoMode>().is_enabled()
+}
+
+// Timestamps are stored as fixed-width UTC so they compare correctly as text in range queries
+fn normalize_timestamp(timestamp: &str) -> Result<String, String> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|time| time.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::Millis, true))
+        .map_err(|e| format!("Invalid timestamp {}: {}", timestamp, e))
+}
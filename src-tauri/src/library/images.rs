@@ -0,0 +1,200 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+// Enough of a file's start to recognise every format below
+pub const SNIFF_LENGTH: usize = 32;
+
+// Page image formats, recognised by their magic bytes rather than their file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Gif,
+    WebP,
+    Bmp,
+    Tiff,
+    Avif,
+    JpegXl,
+    Jpeg2000,
+}
+
+impl ImageFormat {
+    const ALL: [ImageFormat; 9] = [
+        ImageFormat::Jpeg,
+        ImageFormat::Png,
+        ImageFormat::Gif,
+        ImageFormat::WebP,
+        ImageFormat::Bmp,
+        ImageFormat::Tiff,
+        ImageFormat::Avif,
+        ImageFormat::JpegXl,
+        ImageFormat::Jpeg2000,
+    ];
+
+    pub fn sniff(header: &[u8]) -> Option<ImageFormat> {
+        let starts = |magic: &[u8]| header.starts_with(magic);
+
+        if starts(b"\xff\xd8\xff") {
+            Some(ImageFormat::Jpeg)
+        } else if starts(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageFormat::Png)
+        } else if starts(b"GIF87a") || starts(b"GIF89a") {
+            Some(ImageFormat::Gif)
+        } else if starts(b"RIFF") && header.get(8..12) == Some(b"WEBP") {
+            Some(ImageFormat::WebP)
+        } else if starts(b"BM") && is_bmp_header(header) {
+            Some(ImageFormat::Bmp)
+        } else if starts(b"II*\0") || starts(b"MM\0*") {
+            Some(ImageFormat::Tiff)
+        } else if header.get(4..8) == Some(b"ftyp")
+            && matches!(header.get(8..12), Some(b"avif") | Some(b"avis"))
+        {
+            Some(ImageFormat::Avif)
+        } else if starts(b"\xff\x0a") || starts(b"\0\0\0\x0cJXL \r\n\x87\n") {
+            Some(ImageFormat::JpegXl)
+        } else if starts(b"\0\0\0\x0cjP  \r\n\x87\n") || starts(b"\xff\x4f\xff\x51") {
+            Some(ImageFormat::Jpeg2000)
+        } else {
+            None
+        }
+    }
+
+    // Extensions the format is commonly saved with; the first one is used for renamed pages
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            ImageFormat::Jpeg => &["jpg", "jpeg"],
+            ImageFormat::Png => &["png"],
+            ImageFormat::Gif => &["gif"],
+            ImageFormat::WebP => &["webp"],
+            ImageFormat::Bmp => &["bmp"],
+            ImageFormat::Tiff => &["tif", "tiff"],
+            ImageFormat::Avif => &["avif"],
+            ImageFormat::JpegXl => &["jxl"],
+            ImageFormat::Jpeg2000 => &["jp2", "j2k"],
+        }
+    }
+
+    // Format implied by a file name, for pages already in the library
+    pub fn from_file_name(name: &str) -> Option<ImageFormat> {
+        let extension = Path::new(name).extension()?.to_string_lossy().to_lowercase();
+        ImageFormat::ALL
+            .into_iter()
+            .find(|format| format.extensions().contains(&extension.as_str()))
+    }
+
+    // Formats the `image` crate is built to decode (see Cargo.toml). The others are still
+    // pages the reader can show, but can't become covers since thumbnails are made from them
+    pub fn is_decodable(self) -> bool {
+        !matches!(self, ImageFormat::Avif | ImageFormat::JpegXl | ImageFormat::Jpeg2000)
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Png => "image/png",
+            ImageFormat::Gif => "image/gif",
+            ImageFormat::WebP => "image/webp",
+            ImageFormat::Bmp => "image/bmp",
+            ImageFormat::Tiff => "image/tiff",
+            ImageFormat::Avif => "image/avif",
+            ImageFormat::JpegXl => "image/jxl",
+            ImageFormat::Jpeg2000 => "image/jp2",
+        }
+    }
+
+    // File name with an extension matching the content, keeping the original when it fits
+    pub fn file_name(self, name: &Path) -> String {
+        let extension = name
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if self.extensions().contains(&extension.as_str()) {
+            return name.to_string_lossy().to_string();
+        }
+
+        // A misnamed "page.jpg" holding a PNG becomes "page.png"; "001" or "Ch 1.5" get one added
+        let is_image_extension = ImageFormat::ALL
+            .iter()
+            .any(|format| format.extensions().contains(&extension.as_str()));
+        let stem = if is_image_extension {
+            name.file_stem().unwrap_or(name.as_os_str())
+        } else {
+            name.as_os_str()
+        };
+        format!("{}.{}", stem.to_string_lossy(), self.extensions()[0])
+    }
+}
+
+// "BM" starts plenty of other files, so the file header's fields have to be plausible too:
+// a known DIB header size, pixel data after the headers and a file size that covers it
+fn is_bmp_header(header: &[u8]) -> bool {
+    let field = |offset: usize| {
+        header
+            .get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+
+    match (field(2), field(10), field(14)) {
+        (Some(file_size), Some(data_offset), Some(dib_size)) => {
+            matches!(dib_size, 12 | 40 | 52 | 56 | 64 | 108 | 124)
+                && data_offset >= 14 + dib_size
+                && (file_size == 0 || file_size >= data_offset)
+        }
+        _ => false,
+    }
+}
+
+// Sniff a file on disk, for pages that were never renamed by an import
+pub fn sniff_file(path: &Path) -> io::Result<Option<ImageFormat>> {
+    let mut file = fs::File::open(path)?;
+    Ok(ImageFormat::sniff(&read_header(&mut file)?))
+}
+
+// Whether a file can be used as a cover, judged by its content
+pub fn is_cover_file(path: &Path) -> bool {
+    matches!(sniff_file(path), Ok(Some(format)) if format.is_decodable())
+}
+
+// Read the start of a stream for sniffing; the returned bytes still have to be written out
+pub fn read_header(data: &mut dyn Read) -> io::Result<Vec<u8>> {
+    let mut header = vec![0u8; SNIFF_LENGTH];
+    let mut read = 0;
+    while read < header.len() {
+        match data.read(&mut header[read..])? {
+            0 => break,
+            n => read += n,
+        }
+    }
+    header.truncate(read);
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // File header of a 2x2, 24-bit BMP: 70 bytes in total, pixels after a 40-byte DIB header
+    const BMP_HEADER: &[u8] = b"BM\x46\0\0\0\0\0\0\0\x36\0\0\0\
+        \x28\0\0\0\x02\0\0\0\x02\0\0\0\x01\0\x18\0";
+
+    #[test]
+    fn sniffs_bmp_by_its_header_fields() {
+        assert_eq!(ImageFormat::sniff(BMP_HEADER), Some(ImageFormat::Bmp));
+        assert_eq!(ImageFormat::sniff(&BMP_HEADER[..14]), None);
+        assert_eq!(ImageFormat::sniff(b"BMW service notes, 2019 edition"), None);
+
+        // Pixel data can't start inside the headers
+        let mut header = BMP_HEADER.to_vec();
+        header[10] = 0x20;
+        assert_eq!(ImageFormat::sniff(&header), None);
+    }
+
+    #[test]
+    fn sniffs_by_magic_bytes_not_name() {
+        assert_eq!(ImageFormat::sniff(b"\x89PNG\r\n\x1a\n...."), Some(ImageFormat::Png));
+        assert_eq!(ImageFormat::sniff(b"RIFF\0\0\0\0WEBPVP8 "), Some(ImageFormat::WebP));
+        assert_eq!(ImageFormat::sniff(b"<?xml version=\"1.0\"?>"), None);
+        assert_eq!(ImageFormat::from_file_name("001.JPEG"), Some(ImageFormat::Jpeg));
+    }
+}
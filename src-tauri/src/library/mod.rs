@@ -1,6 +1,21 @@
+pub mod archive;
+pub mod categories;
+pub mod comicinfo;
 pub mod commands;
+pub mod covers;
+pub mod db;
+pub mod epub;
+pub mod hashes;
+pub mod history;
+pub mod images;
 pub mod models;
+pub mod pdf;
+pub mod progress;
+pub mod reading;
 pub mod service;
+pub mod stored_archive;
+pub mod thumbnails;
+pub mod util;
 
 // Re-export command handlers
 pub use commands::*;
\ No newline at end of file
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -10,6 +11,49 @@ pub struct MangaInput {
     pub title: String,
     pub path: String,
     pub category: String,
+    // Reference the source in place instead of copying it into the library
+    #[serde(default)]
+    pub link: bool,
+    // Identifies the import in progress events and `cancel_import`; generated when missing
+    #[serde(default)]
+    pub import_id: Option<String>,
+    #[serde(default)]
+    pub on_duplicate: DuplicatePolicy,
+    // Keep a CBZ as-is and read pages out of it; other formats are still extracted
+    #[serde(default)]
+    pub keep_archive: bool,
+}
+
+// What to do when the imported content is already in the library
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    // Import anyway and name the existing entry in the result
+    #[default]
+    Report,
+    // Fail the import, naming the existing entry in the error
+    Refuse,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ImportResult {
+    pub manga: Manga,
+    // Existing manga with the same content, if any
+    pub duplicate_of: Option<DuplicateMatch>,
+    // Files left out of the import, e.g. anything that isn't a page image
+    pub skipped: Vec<SkippedFile>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DuplicateMatch {
+    pub id: String,
+    pub title: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -22,5 +66,251 @@ pub struct Manga {
     pub last_read: Option<String>,
     pub created_at: String,
     pub progress: u32,
+    // "local" or an extension ID; the frontend reads it as `source_id`, older entries say `source`
+    #[serde(rename = "source_id", alias = "source")]
+    pub source: String,
+    #[serde(default)]
+    pub chapters: Vec<Chapter>,
+    #[serde(default)]
+    pub metadata: MangaMetadata,
+    // `path` points at the user's own folder or archive rather than a copy under app data
+    #[serde(default)]
+    pub linked: bool,
+    // Set when a linked source could not be found the last time it was opened
+    #[serde(default)]
+    pub source_missing: bool,
+    // CBZ or CBR the pages are served from instead of files under `path`
+    #[serde(default)]
+    pub archive: Option<String>,
+}
+
+// A manga from an extension, added to the library without importing any files
+#[derive(Debug, Deserialize)]
+pub struct OnlineMangaInput {
+    // The extension's own ID, kept so the manga can be looked up again; generated when missing
+    #[serde(default)]
+    pub id: Option<String>,
+    pub title: String,
+    #[serde(default)]
+    pub cover: String,
+    pub category: String,
+    #[serde(default)]
+    pub path: String,
+    pub source_id: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+// Search, filters, sort and page for `query_library`; everything is optional
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct LibraryQuery {
+    // Words matched against titles, authors and tags, each as a prefix
+    pub search: Option<String>,
+    pub category: Option<String>,
+    // "local" or an extension ID
+    pub source: Option<String>,
+    pub read_status: Option<ReadStatus>,
+    // Only adult-rated manga when true, none of them when false
+    pub nsfw: Option<bool>,
+    pub sort: LibrarySort,
+    // Defaults to A to Z for titles and newest or most first for the other sorts
+    pub descending: Option<bool>,
+    // All matches when missing
+    pub limit: Option<u32>,
+    pub offset: u32,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadStatus {
+    Unread,
+    Reading,
+    Completed,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LibrarySort {
+    #[default]
+    Title,
+    LastRead,
+    DateAdded,
+    // Chapters not read to the end
+    Unread,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LibraryPage {
+    pub manga: Vec<Manga>,
+    // Matches across all pages
+    pub total: u32,
+}
+
+// Changes to a manga's details; fields left out are kept as they are
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct MangaUpdate {
+    pub title: Option<String>,
+    pub category: Option<String>,
+    // Path of an image file to use as the cover, as with `CoverSource::File`
+    pub cover: Option<String>,
+    // Tidied up and checked the same way as `update_manga_metadata`
+    pub metadata: Option<MetadataUpdate>,
+}
+
+// Every field has a default, so entries saved before a field existed still load
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct MangaMetadata {
+    // Writers, from EPUB creators or ComicInfo <Writer>
+    pub authors: Vec<String>,
+    // Pencillers, from ComicInfo <Penciller>
+    pub artists: Vec<String>,
+    // "left-to-right" or "right-to-left", matching the reader's reading modes
+    pub reading_direction: Option<String>,
+    pub series: Option<String>,
+    // Other names the series goes by, e.g. the original-language title
+    pub alternative_titles: Vec<String>,
+    // Issue number as written, e.g. "12" or "12.5"
+    pub number: Option<String>,
+    pub volume: Option<u32>,
+    // Saved as `summary` before it was renamed
+    #[serde(alias = "summary")]
+    pub description: Option<String>,
+    pub genres: Vec<String>,
+    pub tags: Vec<String>,
+    pub status: PublicationStatus,
+    // ISO language code
+    pub language: Option<String>,
+    pub age_rating: Option<String>,
+    // IDs on other sites by site name, e.g. "anilist" or "mal"
+    pub external_ids: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PublicationStatus {
+    #[default]
+    Unknown,
+    Ongoing,
+    Completed,
+    Hiatus,
+    Cancelled,
+}
+
+// Changes to a manga's metadata; fields left out are kept, and an empty text field clears it
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct MetadataUpdate {
+    pub authors: Option<Vec<String>>,
+    pub artists: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub genres: Option<Vec<String>>,
+    pub tags: Option<Vec<String>>,
+    pub status: Option<PublicationStatus>,
+    pub alternative_titles: Option<Vec<String>>,
+    pub language: Option<String>,
+    pub reading_direction: Option<String>,
+    // Replaces all external IDs
+    pub external_ids: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Chapter {
+    pub id: String,
+    pub title: String,
+    pub number: Option<f32>,
+    pub volume: Option<f32>,
+    pub page_count: u32,
+    // Chapter directory relative to the manga path ("" for a flat manga)
+    pub path: String,
+    // Manual page order (file names); pages not listed follow in natural order
+    #[serde(default)]
+    pub page_order: Vec<String>,
+}
+
+// A place in a manga to open the reader at
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReadingPosition {
+    pub chapter_id: String,
+    // Zero-based page index within the chapter
+    pub page: u32,
+    // Pixels scrolled within the page, for the webtoon reading mode
+    #[serde(default)]
+    pub scroll_offset: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ReadingProgress {
+    pub manga_id: String,
+    // Percentage of the manga's pages read
+    pub progress: u32,
+    pub last_read: Option<String>,
+    // Where to pick up: the last position, or the next chapter once that one was finished
+    pub continue_reading: Option<ReadingPosition>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Category {
+    pub id: String,
+    pub name: String,
+    // Number of manga in the category, filled in when listing
+    #[serde(default)]
+    pub manga_count: u32,
+}
+
+// One reading session of a chapter
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    // Assigned when the session is recorded
+    #[serde(default)]
+    pub id: i64,
+    pub manga_id: String,
+    // Title and cover are kept so the entry still shows once the manga left the library
+    pub manga_title: String,
+    #[serde(default)]
+    pub cover: String,
+    pub chapter_id: String,
+    #[serde(default)]
+    pub chapter_title: String,
+    pub pages_read: u32,
+    // RFC 3339 timestamps
+    pub started_at: String,
+    pub ended_at: String,
+    // "local" or an extension ID
     pub source: String,
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PageList {
+    // Directory the page file names are relative to
+    pub base_url: String,
+    pub pages: Vec<String>,
+    // URI scheme that serves `base_url/page`: "asset" for files, or the stored archive protocol
+    pub protocol: String,
+}
+
+// Where a custom cover comes from
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CoverSource {
+    // A page of one of the manga's chapters, optionally cropped
+    Page {
+        chapter_id: String,
+        page: String,
+        #[serde(default)]
+        crop: Option<CoverCrop>,
+    },
+    // An image file anywhere on disk
+    File { path: String },
+}
+
+// Pixel rectangle of a page to keep
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct CoverCrop {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
@@ -0,0 +1,313 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use png::{BitDepth, ColorType, Encoder};
+use crate::library::progress::ImportTracker;
+
+// Stream filters lopdf can undo for us before we re-encode raw pixels
+const DECODABLE_FILTERS: [&[u8]; 3] = [b"FlateDecode", b"LZWDecode", b"ASCII85Decode"];
+
+// Extract the raster page images of a PDF, one file per page in page order
+pub fn extract_pdf_images(
+    pdf_path: &Path,
+    destination: &Path,
+    tracker: &mut ImportTracker,
+) -> Result<(), String> {
+    let document =
+        Document::load(pdf_path).map_err(|e| format!("Failed to read PDF file: {}", e))?;
+
+    let mut extracted = 0;
+    for (page_number, page_id) in document.get_pages() {
+        tracker.check_cancelled()?;
+
+        // Pages without an image XObject are vector-only and are skipped
+        let page_name = format!("page {}", page_number);
+        let images = page_images(&document, page_id);
+
+        // Scanned pages carry one full-page image; for several, keep the largest
+        let image = match images.iter().max_by_key(|image| image.width * image.height) {
+            Some(image) => image,
+            None => {
+                tracker.file_skipped(&page_name, "no embedded page image");
+                continue;
+            }
+        };
+
+        let file_stem = format!("{:04}", page_number);
+
+        let written = match image.filters.as_slice() {
+            // JPEG streams are complete image files already
+            [b"DCTDecode"] => write_page(destination, &file_stem, "jpg", &image.stream.content)?,
+            // The webview can't show JPEG 2000 and there is no decoder to convert it with
+            [b"JPXDecode"] => {
+                tracker.file_skipped(&page_name, "JPEG 2000 page images are not supported");
+                continue;
+            }
+            filters
+                if filters
+                    .iter()
+                    .all(|filter| DECODABLE_FILTERS.contains(filter)) =>
+            {
+                let pixels = if filters.is_empty() {
+                    image.stream.content.clone()
+                } else {
+                    image.stream.decompressed_content().map_err(|e| {
+                        format!("Failed to decompress image on page {}: {}", page_number, e)
+                    })?
+                };
+
+                // Sub-byte RGB and CMYK images don't occur in practice, so only gray may be 1-bit
+                let color = pixel_color(&document, &image.stream.dict);
+                let (color_type, bit_depth, data) = match (color, image.bits_per_component) {
+                    (Some(PixelColor::Gray), Some(1)) => {
+                        (ColorType::Grayscale, BitDepth::One, pixels)
+                    }
+                    (Some(PixelColor::Gray), Some(8) | None) => {
+                        (ColorType::Grayscale, BitDepth::Eight, pixels)
+                    }
+                    (Some(PixelColor::Rgb), Some(8) | None) => {
+                        (ColorType::Rgb, BitDepth::Eight, pixels)
+                    }
+                    (Some(PixelColor::Cmyk), Some(8) | None) => {
+                        (ColorType::Rgb, BitDepth::Eight, cmyk_to_rgb(&pixels))
+                    }
+                    _ => {
+                        tracker.file_skipped(&page_name, "unsupported color space or bit depth");
+                        continue;
+                    }
+                };
+
+                write_png_page(
+                    destination,
+                    &file_stem,
+                    (image.width as u32, image.height as u32),
+                    color_type,
+                    bit_depth,
+                    &data,
+                )?
+            }
+            // CCITT, JBIG2 and chained filters aren't supported
+            filters => {
+                let names: Vec<String> = filters
+                    .iter()
+                    .map(|filter| String::from_utf8_lossy(filter).to_string())
+                    .collect();
+                let reason = format!("unsupported image encoding ({})", names.join(", "));
+                tracker.file_skipped(&page_name, &reason);
+                continue;
+            }
+        };
+
+        let bytes = fs::metadata(&written).map(|meta| meta.len()).unwrap_or(0);
+        tracker.file_written(&written.to_string_lossy(), bytes);
+        extracted += 1;
+    }
+
+    if extracted == 0 {
+        return Err(
+            "PDF contains no extractable page images; vector-only PDFs are not supported"
+                .to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+// An image XObject drawn on a page
+struct PageImage<'a> {
+    width: i64,
+    height: i64,
+    bits_per_component: Option<i64>,
+    filters: Vec<&'a [u8]>,
+    stream: &'a Stream,
+}
+
+// Every image a page draws, including those inside Form XObjects; scanners often wrap the
+// page image in a form, and keep the resources on the page tree instead of each page
+fn page_images(document: &Document, page_id: ObjectId) -> Vec<PageImage<'_>> {
+    let mut images = Vec::new();
+    let resources = document
+        .get_dictionary(page_id)
+        .ok()
+        .and_then(|page| inherited_resources(document, page));
+    if let Some(resources) = resources {
+        collect_images(document, resources, &mut HashSet::new(), &mut images);
+    }
+    images
+}
+
+// A page's own resources, or those of its nearest ancestor in the page tree
+fn inherited_resources<'a>(document: &'a Document, page: &'a Dictionary) -> Option<&'a Dictionary> {
+    let mut node = page;
+    let mut seen = HashSet::new();
+    loop {
+        if let Ok(resources) = node.get(b"Resources") {
+            return resolve_dict(document, resources);
+        }
+        let parent_id = node.get(b"Parent").and_then(Object::as_reference).ok()?;
+        if !seen.insert(parent_id) {
+            return None;
+        }
+        node = document.get_dictionary(parent_id).ok()?;
+    }
+}
+
+fn collect_images<'a>(
+    document: &'a Document,
+    resources: &'a Dictionary,
+    seen: &mut HashSet<ObjectId>,
+    images: &mut Vec<PageImage<'a>>,
+) {
+    let xobjects = match resources.get(b"XObject").ok().and_then(|x| resolve_dict(document, x)) {
+        Some(xobjects) => xobjects,
+        None => return,
+    };
+
+    for (_, value) in xobjects.iter() {
+        // Forms may be shared between pages or even draw themselves
+        let id = match value.as_reference() {
+            Ok(id) if seen.insert(id) => id,
+            _ => continue,
+        };
+        let stream = match document.get_object(id).and_then(Object::as_stream) {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+
+        match stream.dict.get(b"Subtype").and_then(Object::as_name) {
+            Ok(b"Image") => images.extend(PageImage::from_stream(stream)),
+            // A form without resources of its own uses those it is drawn with
+            Ok(b"Form") => {
+                let form_resources = stream
+                    .dict
+                    .get(b"Resources")
+                    .ok()
+                    .and_then(|form_resources| resolve_dict(document, form_resources))
+                    .unwrap_or(resources);
+                collect_images(document, form_resources, seen, images);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'a> PageImage<'a> {
+    fn from_stream(stream: &'a Stream) -> Option<PageImage<'a>> {
+        Some(PageImage {
+            width: stream.dict.get(b"Width").and_then(Object::as_i64).ok()?,
+            height: stream.dict.get(b"Height").and_then(Object::as_i64).ok()?,
+            bits_per_component: stream.dict.get(b"BitsPerComponent").and_then(Object::as_i64).ok(),
+            // No filter at all means raw pixels
+            filters: stream.filters().unwrap_or_default(),
+            stream,
+        })
+    }
+}
+
+fn resolve_dict<'a>(document: &'a Document, object: &'a Object) -> Option<&'a Dictionary> {
+    match object {
+        Object::Reference(id) => document.get_dictionary(*id).ok(),
+        Object::Dictionary(dict) => Some(dict),
+        _ => None,
+    }
+}
+
+// Resolve the image color space to the pixel layouts we can re-encode
+fn pixel_color(document: &Document, dict: &Dictionary) -> Option<PixelColor> {
+    let color_space = dict.get(b"ColorSpace").ok()?;
+    let color_space = match color_space {
+        Object::Reference(id) => document.get_object(*id).ok()?,
+        other => other,
+    };
+
+    match color_space {
+        Object::Name(name) => PixelColor::from_name(name),
+        Object::Array(array) => match array.first()?.as_name().ok()? {
+            // ICC profiles are identified by their component count
+            b"ICCBased" => {
+                let profile = document
+                    .get_object(array.get(1)?.as_reference().ok()?)
+                    .and_then(Object::as_stream)
+                    .ok()?;
+                match profile.dict.get(b"N").and_then(Object::as_i64).ok()? {
+                    1 => Some(PixelColor::Gray),
+                    3 => Some(PixelColor::Rgb),
+                    4 => Some(PixelColor::Cmyk),
+                    _ => None,
+                }
+            }
+            name => PixelColor::from_name(name),
+        },
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy)]
+enum PixelColor {
+    Gray,
+    Rgb,
+    Cmyk,
+}
+
+impl PixelColor {
+    fn from_name(name: &[u8]) -> Option<PixelColor> {
+        match name {
+            b"DeviceGray" | b"CalGray" => Some(PixelColor::Gray),
+            b"DeviceRGB" | b"CalRGB" => Some(PixelColor::Rgb),
+            b"DeviceCMYK" => Some(PixelColor::Cmyk),
+            _ => None,
+        }
+    }
+}
+
+// Encode raw PDF pixel data as a PNG page
+fn write_png_page(
+    destination: &Path,
+    file_stem: &str,
+    (width, height): (u32, u32),
+    color_type: ColorType,
+    bit_depth: BitDepth,
+    data: &[u8],
+) -> Result<PathBuf, String> {
+    let dest_path = destination.join(format!("{}.png", file_stem));
+    let file =
+        fs::File::create(&dest_path).map_err(|e| format!("Failed to create output file: {}", e))?;
+
+    let mut encoder = Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(color_type);
+    encoder.set_depth(bit_depth);
+
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("Failed to encode page image: {}", e))?;
+    writer
+        .write_image_data(data)
+        .map_err(|e| format!("Failed to encode page image: {}", e))?;
+
+    Ok(dest_path)
+}
+
+// Naive CMYK to RGB conversion, good enough for page previews
+fn cmyk_to_rgb(pixels: &[u8]) -> Vec<u8> {
+    pixels
+        .chunks_exact(4)
+        .flat_map(|cmyk| {
+            let k = 255 - cmyk[3] as u32;
+            [0, 1, 2].map(|channel| ((255 - cmyk[channel] as u32) * k / 255) as u8)
+        })
+        .collect()
+}
+
+// Write an already-encoded page image
+fn write_page(
+    destination: &Path,
+    file_stem: &str,
+    extension: &str,
+    data: &[u8],
+) -> Result<PathBuf, String> {
+    let dest_path = destination.join(format!("{}.{}", file_stem, extension));
+    fs::write(&dest_path, data).map_err(|e| format!("Failed to write file data: {}", e))?;
+    Ok(dest_path)
+}
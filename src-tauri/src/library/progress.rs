@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime};
+use crate::library::models::SkippedFile;
+
+pub const IMPORT_PROGRESS_EVENT: &str = "import-progress";
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ImportProgress {
+    pub import_id: String,
+    pub entries_processed: u64,
+    pub bytes_written: u64,
+    pub files_skipped: u64,
+    pub current_file: String,
+}
+
+// Cancellation flags of the imports currently running, keyed by import id
+#[derive(Default)]
+pub struct ImportRegistry {
+    imports: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl ImportRegistry {
+    pub fn register(&self, import_id: &str) -> Arc<AtomicBool> {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.imports
+            .lock()
+            .unwrap()
+            .insert(import_id.to_string(), cancelled.clone());
+        cancelled
+    }
+
+    pub fn unregister(&self, import_id: &str) {
+        self.imports.lock().unwrap().remove(import_id);
+    }
+
+    // Register an import that stays cancellable until the returned guard is dropped, so every
+    // step of it, hashing included, can be stopped
+    pub fn start(&self, import_id: &str) -> RunningImport<'_> {
+        RunningImport {
+            registry: self,
+            import_id: import_id.to_string(),
+            cancelled: self.register(import_id),
+        }
+    }
+
+    // Returns false when no import with that id is running
+    pub fn cancel(&self, import_id: &str) -> bool {
+        match self.imports.lock().unwrap().get(import_id) {
+            Some(cancelled) => {
+                cancelled.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub struct RunningImport<'a> {
+    registry: &'a ImportRegistry,
+    import_id: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl RunningImport<'_> {
+    pub fn cancelled(&self) -> Arc<AtomicBool> {
+        self.cancelled.clone()
+    }
+}
+
+impl Drop for RunningImport<'_> {
+    fn drop(&mut self) {
+        self.registry.unregister(&self.import_id);
+    }
+}
+
+// Reports per-file progress from the copy and extraction loops and lets them stop early
+pub struct ImportTracker {
+    progress: ImportProgress,
+    skipped: Vec<SkippedFile>,
+    cancelled: Arc<AtomicBool>,
+    emit: Box<dyn Fn(&ImportProgress)>,
+}
+
+impl ImportTracker {
+    pub fn new<R: Runtime>(app: &AppHandle<R>, import_id: &str, cancelled: Arc<AtomicBool>) -> Self {
+        let app = app.clone();
+        ImportTracker {
+            progress: ImportProgress {
+                import_id: import_id.to_string(),
+                ..ImportProgress::default()
+            },
+            skipped: Vec::new(),
+            cancelled,
+            emit: Box::new(move |progress| {
+                if let Err(err) = app.emit(IMPORT_PROGRESS_EVENT, progress.clone()) {
+                    println!("Failed to emit import progress: {}", err);
+                }
+            }),
+        }
+    }
+
+    // Bail out of the current loop once the user cancelled the import
+    pub fn check_cancelled(&self) -> Result<(), String> {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return Err("Import cancelled".to_string());
+        }
+        Ok(())
+    }
+
+    // Record a file that was just written
+    pub fn file_written(&mut self, file_name: &str, bytes: u64) {
+        self.progress.entries_processed += 1;
+        self.progress.bytes_written += bytes;
+        self.progress.current_file = file_name.to_string();
+        (self.emit)(&self.progress);
+    }
+
+    // Record a file that was left out of the import
+    pub fn file_skipped(&mut self, file_name: &str, reason: &str) {
+        self.progress.entries_processed += 1;
+        self.progress.files_skipped += 1;
+        self.progress.current_file = file_name.to_string();
+        self.skipped.push(SkippedFile {
+            path: file_name.to_string(),
+            reason: reason.to_string(),
+        });
+        (self.emit)(&self.progress);
+    }
+
+    pub fn into_skipped(self) -> Vec<SkippedFile> {
+        self.skipped
+    }
+}
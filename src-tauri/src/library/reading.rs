@@ -0,0 +1,110 @@
+use tauri::{AppHandle, Manager, Runtime};
+use chrono;
+use crate::library::db::{ChapterPosition, Database};
+use crate::library::models::{Chapter, ReadingPosition, ReadingProgress};
+use crate::library::service;
+
+// Save the reader's position and recompute the manga's progress from it
+pub fn record_reading_progress<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    mut position: ReadingPosition,
+) -> Result<ReadingProgress, String> {
+    let manga = service::find_manga(&app, manga_id)?;
+
+    // Local manga without chapters are read as one, whatever ID the reader made up for it.
+    // Online manga keep the source's chapter IDs, since the library has no chapters for them
+    if manga.chapters.is_empty() {
+        if manga.source == "local" {
+            position.chapter_id = String::new();
+        }
+    } else {
+        let chapter = manga
+            .chapters
+            .iter()
+            .find(|chapter| chapter.id == position.chapter_id)
+            .ok_or_else(|| format!("Chapter with ID {} not found", position.chapter_id))?;
+        position.page = position.page.min(chapter.page_count.saturating_sub(1));
+    }
+
+    let database = app.state::<Database>();
+    let read_at = chrono::Utc::now().to_string();
+    database.save_position(manga_id, &position, &read_at)?;
+    let positions = database.positions(manga_id)?;
+
+    // Online manga have no page counts in the library and keep their progress as it was
+    let progress = progress_percent(&manga.chapters, &positions).unwrap_or(manga.progress);
+    database.set_progress(manga_id, progress, &read_at)?;
+
+    Ok(ReadingProgress {
+        manga_id: manga.id,
+        progress,
+        last_read: Some(read_at),
+        continue_reading: continue_target(&manga.chapters, &positions),
+    })
+}
+
+pub fn get_reading_progress<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+) -> Result<ReadingProgress, String> {
+    let manga = service::find_manga(&app, manga_id)?;
+    let positions = app.state::<Database>().positions(manga_id)?;
+
+    Ok(ReadingProgress {
+        continue_reading: continue_target(&manga.chapters, &positions),
+        manga_id: manga.id,
+        progress: manga.progress,
+        last_read: manga.last_read,
+    })
+}
+
+// Share of all pages up to the furthest page reached in each chapter
+fn progress_percent(chapters: &[Chapter], positions: &[ChapterPosition]) -> Option<u32> {
+    let total: u64 = chapters.iter().map(|chapter| chapter.page_count as u64).sum();
+    if total == 0 {
+        return None;
+    }
+
+    let read: u64 = chapters
+        .iter()
+        .filter_map(|chapter| {
+            let position = positions.iter().find(|position| position.chapter_id == chapter.id)?;
+            Some((position.furthest_page as u64 + 1).min(chapter.page_count as u64))
+        })
+        .sum();
+    Some((read * 100 / total) as u32)
+}
+
+// The most recent position, moving on to the next chapter when it was on the last page
+fn continue_target(chapters: &[Chapter], positions: &[ChapterPosition]) -> Option<ReadingPosition> {
+    let latest = match positions.first() {
+        Some(latest) => latest,
+        // Nothing read yet starts at the beginning
+        None => {
+            return chapters.first().map(|chapter| ReadingPosition {
+                chapter_id: chapter.id.clone(),
+                page: 0,
+                scroll_offset: 0.0,
+            })
+        }
+    };
+
+    let index = chapters.iter().position(|chapter| chapter.id == latest.chapter_id);
+    if let Some(index) = index {
+        let finished = latest.page + 1 >= chapters[index].page_count;
+        if let Some(next) = chapters.get(index + 1).filter(|_| finished) {
+            return Some(ReadingPosition {
+                chapter_id: next.id.clone(),
+                page: 0,
+                scroll_offset: 0.0,
+            });
+        }
+    }
+
+    Some(ReadingPosition {
+        chapter_id: latest.chapter_id.clone(),
+        page: latest.page,
+        scroll_offset: latest.scroll_offset,
+    })
+}
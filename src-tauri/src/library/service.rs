@@ -1,22 +1,303 @@
 use tauri::{AppHandle, Runtime};
 use tauri::Manager;
 use std::fs;
-use std::path::Path;
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
 use nanoid::nanoid;
 use chrono;
-use zip::ZipArchive;
-use crate::library::models::{Manga, MangaInput, Library};
+use crate::library::{archive, categories, covers, epub, hashes, pdf, stored_archive, thumbnails};
+use crate::library::comicinfo::ComicInfo;
+use crate::library::images::{self, ImageFormat};
+use crate::library::db::Database;
+use crate::library::hashes::HashEntry;
+use crate::library::progress::{ImportRegistry, ImportTracker};
+use crate::library::stored_archive::{ArchiveCache, StoredArchive};
+use crate::library::thumbnails::ThumbnailSize;
+use crate::library::models::{
+    Chapter, CoverSource, DuplicateMatch, DuplicatePolicy, ImportResult, Library, LibraryPage,
+    LibraryQuery, Manga, MangaInput, MangaMetadata, MangaUpdate, MetadataUpdate,
+    OnlineMangaInput, PageList, SkippedFile,
+};
 
-pub fn delete_manga(path: &str) -> Result<(), String> {
-    fs::remove_dir_all(path)
-        .map_err(|err| format!("Failed to delete manga: {}", err))?;
+// Delete a manga by ID, together with its files when the library owns them. The directory is
+// moved aside first and only deleted once the entry is gone, so neither outlives the other
+pub fn delete_manga<R: Runtime>(app: AppHandle<R>, manga_id: &str) -> Result<(), String> {
+    let manga = find_manga(&app, manga_id)?;
+    let app_data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
+    let manga_dir = owned_manga_dir(&app_data_dir, &manga)?;
+
+    // An open archive would keep its file locked on Windows
+    app.state::<ArchiveCache>().evict(manga_id);
+
+    let deleting_dir = match &manga_dir {
+        Some(manga_dir) => {
+            let deleting_dir = manga_dir.with_file_name(format!(".{}.deleting", manga.id));
+            fs::rename(manga_dir, &deleting_dir)
+                .map_err(|e| format!("Failed to delete manga: {}", e))?;
+            Some(deleting_dir)
+        }
+        None => None,
+    };
+
+    let removed = app.state::<Database>().remove_manga(manga_id).and_then(|removed| {
+        removed
+            .then_some(())
+            .ok_or_else(|| format!("Manga with ID {} not found", manga_id))
+    });
+    if let Err(err) = removed {
+        // Put the files back so the entry still points at them
+        if let (Some(manga_dir), Some(deleting_dir)) = (&manga_dir, &deleting_dir) {
+            if let Err(err) = fs::rename(deleting_dir, manga_dir) {
+                println!("Failed to restore {}: {}", manga_dir.display(), err);
+            }
+        }
+        return Err(err);
+    }
+
+    if let Some(deleting_dir) = deleting_dir {
+        if let Err(err) = fs::remove_dir_all(&deleting_dir) {
+            println!("Failed to clean up {}: {}", deleting_dir.display(), err);
+        }
+    }
+    thumbnails::invalidate_thumbnails(&app_data_dir, manga_id);
+    covers::remove_covers(&app_data_dir.join("covers"), manga_id);
+    stored_archive::remove_unpacked(&app_data_dir, manga_id);
     Ok(())
 }
 
+// The manga's directory when it is one the library created, resolved through symlinks and
+// "..". Linked folders belong to the user and online manga own no files, whatever their path
+// holds; anything else that isn't directly inside the library directory is refused
+fn owned_manga_dir(app_data_dir: &Path, manga: &Manga) -> Result<Option<PathBuf>, String> {
+    if manga.source != "local" || manga.linked || manga.path.is_empty() {
+        return Ok(None);
+    }
+
+    // Files that are already gone leave only the entry to remove
+    let manga_dir = match Path::new(&manga.path).canonicalize() {
+        Ok(manga_dir) => manga_dir,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("Failed to resolve {}: {}", manga.path, err)),
+    };
+    let library_root = app_data_dir.join("library").canonicalize().ok();
+
+    if library_root.is_none() || manga_dir.parent() != library_root.as_deref() {
+        return Err(format!(
+            "Refusing to delete {}: it is not inside the library directory",
+            manga.path
+        ));
+    }
+    Ok(Some(manga_dir))
+}
+
 pub fn import_manga_folder<R: Runtime>(
     app: AppHandle<R>,
     manga_input: MangaInput,
-) -> Result<Manga, String> {
+) -> Result<ImportResult, String> {
+    if manga_input.link {
+        return link_manga_folder(app, manga_input);
+    }
+
+    // Source folder path
+    let source_folder = PathBuf::from(&manga_input.path);
+
+    // Copy files from source to destination, keeping the folder structure
+    import_into_library(app, manga_input, None, |manga_dir, tracker| {
+        copy_folder_contents(&source_folder, manga_dir, tracker)?;
+        Ok(ImportDetails::default())
+    })
+}
+
+// Kept for callers of the original CBZ command; the format is detected either way
+pub async fn import_manga_cbz<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: MangaInput,
+) -> Result<ImportResult, String> {
+    import_manga_archive(app, manga_input).await
+}
+
+// Import any supported comic archive (CBZ, CBR, CB7, CBT), detecting its format
+pub async fn import_manga_archive<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: MangaInput,
+) -> Result<ImportResult, String> {
+    if manga_input.link {
+        return link_manga_archive(app, manga_input);
+    }
+
+    let archive_path = manga_input.path.clone();
+    let has_title = !manga_input.title.trim().is_empty();
+    let keep_archive = manga_input.keep_archive
+        && archive::ArchiveFormat::detect(Path::new(&archive_path))? == archive::ArchiveFormat::Zip;
+
+    // Extract the archive to the manga directory, or copy a CBZ there as-is
+    import_into_library(app, manga_input, Some(Path::new(&archive_path)), |manga_dir, tracker| {
+        if keep_archive {
+            return store_archive(Path::new(&archive_path), manga_dir, has_title, tracker);
+        }
+
+        let comic_info = match archive::extract_archive(&archive_path, manga_dir, tracker)? {
+            Some(comic_info) => comic_info,
+            None => return Ok(ImportDetails::default()),
+        };
+
+        // ComicInfo.xml only fills in the title when the user left it empty
+        Ok(ImportDetails {
+            title: comic_info.display_title().filter(|_| !has_title),
+            metadata: comic_info.metadata,
+            ..ImportDetails::default()
+        })
+    })
+}
+
+// Import a directory of volume archives (e.g. "Vol 01.cbz" … "Vol 20.cbz") as one series,
+// with each archive becoming a chapter
+pub async fn import_manga_series<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: MangaInput,
+) -> Result<ImportResult, String> {
+    reject_link(&manga_input)?;
+
+    let series_folder = PathBuf::from(&manga_input.path);
+    let has_title = !manga_input.title.trim().is_empty();
+
+    // Collect the archives in natural order so "Vol 2" comes before "Vol 10"; the content
+    // decides the format later, but only files named as comic archives count as volumes
+    let mut volumes: Vec<PathBuf> = fs::read_dir(&series_folder)
+        .map_err(|err| format!("Failed to read series folder: {}", err))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && archive::ArchiveFormat::from_extension(path).is_some())
+        .collect();
+    volumes.sort_by(|a, b| natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
+
+    if volumes.is_empty() {
+        return Err("No comic archives found in the series folder".to_string());
+    }
+
+    import_into_library(app, manga_input, None, |manga_dir, tracker| {
+        let mut details = ImportDetails::default();
+
+        for (index, volume) in volumes.iter().enumerate() {
+            // Each archive gets its own directory, which chapter detection picks up
+            let volume_name = volume
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| format!("Volume {}", index + 1));
+            // "Vol 1.cbz" and "Vol 1.cbr" would otherwise share a directory
+            let volume_dir = (1..)
+                .map(|n| match n {
+                    1 => manga_dir.join(&volume_name),
+                    n => manga_dir.join(format!("{} ({})", volume_name, n)),
+                })
+                .find(|dir| !dir.exists())
+                .unwrap();
+            fs::create_dir_all(&volume_dir).map_err(|err| err.to_string())?;
+
+            let comic_info =
+                archive::extract_archive(&volume.to_string_lossy(), &volume_dir, tracker)?;
+
+            // Series-level metadata comes from the first volume
+            if index == 0 {
+                if let Some(comic_info) = comic_info {
+                    details.title = comic_info.display_title().filter(|_| !has_title);
+                    details.metadata = comic_info.metadata;
+                }
+            }
+        }
+
+        Ok(details)
+    })
+}
+
+// Import a PDF by extracting its embedded page images
+pub async fn import_manga_pdf<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: MangaInput,
+) -> Result<ImportResult, String> {
+    reject_link(&manga_input)?;
+
+    let pdf_path = PathBuf::from(&manga_input.path);
+
+    import_into_library(app, manga_input, Some(&pdf_path), |manga_dir, tracker| {
+        pdf::extract_pdf_images(&pdf_path, manga_dir, tracker)?;
+        Ok(ImportDetails::default())
+    })
+}
+
+// Import a fixed-layout EPUB, taking title, authors and reading direction from its package
+pub async fn import_manga_epub<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: MangaInput,
+) -> Result<ImportResult, String> {
+    reject_link(&manga_input)?;
+
+    let epub_path = PathBuf::from(&manga_input.path);
+
+    import_into_library(app, manga_input, Some(&epub_path), |manga_dir, tracker| {
+        let package = epub::extract_epub_pages(&epub_path, manga_dir, tracker)?;
+        Ok(ImportDetails {
+            title: package.title,
+            metadata: package.metadata,
+            ..ImportDetails::default()
+        })
+    })
+}
+
+// Copy a CBZ into the manga directory unchanged; pages are served out of it when reading
+fn store_archive(
+    archive_path: &Path,
+    manga_dir: &Path,
+    has_title: bool,
+    tracker: &mut ImportTracker,
+) -> Result<ImportDetails, String> {
+    tracker.check_cancelled()?;
+
+    let file_name = archive_path
+        .file_name()
+        .ok_or("Invalid archive path")?
+        .to_string_lossy()
+        .to_string();
+    let bytes = fs::copy(archive_path, manga_dir.join(&file_name))
+        .map_err(|e| format!("Failed to copy archive: {}", e))?;
+    tracker.file_written(&file_name, bytes);
+
+    let mut archive = StoredArchive::open(&manga_dir.join(&file_name), None)?;
+    for entry in archive.skipped_entries() {
+        tracker.file_skipped(entry, "not a recognised image format");
+    }
+    let comic_info = stored_archive::read_comic_info(&mut archive);
+
+    Ok(ImportDetails {
+        title: comic_info
+            .as_ref()
+            .and_then(ComicInfo::display_title)
+            .filter(|_| !has_title),
+        metadata: comic_info.map(|comic_info| comic_info.metadata).unwrap_or_default(),
+        stored_archive: Some(file_name),
+    })
+}
+
+// Details an importer learned from the source itself
+#[derive(Default)]
+struct ImportDetails {
+    // Replaces the user-typed title when set
+    title: Option<String>,
+    metadata: MangaMetadata,
+    // File name of a CBZ kept as-is in the manga directory
+    stored_archive: Option<String>,
+}
+
+// Shared import flow: fill a fresh library directory, detect chapters and cover, then register
+fn import_into_library<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: MangaInput,
+    // The single file being imported, hashed to catch a re-import before extracting it
+    source_file: Option<&Path>,
+    fill: impl FnOnce(&Path, &mut ImportTracker) -> Result<ImportDetails, String>,
+) -> Result<ImportResult, String> {
+    categories::ensure_category(&app, &manga_input.category)?;
+
     // Generate a unique ID for the manga
     let id = nanoid!();
 
@@ -27,23 +308,85 @@ pub fn import_manga_folder<R: Runtime>(
     let library_path = app_data_dir.join("library");
     fs::create_dir_all(&library_path).map_err(|err| err.to_string())?;
 
-    // Create manga-specific directory
-    let manga_dir = library_path.join(&id);
-    fs::create_dir_all(&manga_dir).map_err(|err| err.to_string())?;
+    // The import can be cancelled from the first hash until it is registered
+    let import_id = manga_input.import_id.clone().unwrap_or_else(|| id.clone());
+    let registry = app.state::<ImportRegistry>();
+    let running = registry.start(&import_id);
+    let mut tracker = ImportTracker::new(&app, &import_id, running.cancelled());
 
-    // Source folder path
-    let source_folder = Path::new(&manga_input.path);
+    // A file imported before is recognised without extracting it again
+    let archive_hash = source_file
+        .map(|path| hashes::hash_file(path, &tracker))
+        .transpose()?;
+    let database = app.state::<Database>();
+    let mut duplicate_of = match &archive_hash {
+        Some(hash) => {
+            check_duplicate(manga_input.on_duplicate, database.find_by_archive_hash(hash)?)?
+        }
+        None => None,
+    };
 
-    // Copy files from source to destination
-    copy_folder_contents(source_folder, &manga_dir).map_err(|err| err.to_string())?;
+    // Stage the pages next to the library so a failed import never shows up inside it
+    let mut staged = StagedDir::create(app_data_dir.join("import-staging").join(&id))?;
 
-    // Find suitable cover image
-    let cover = find_cover_image(&manga_dir).unwrap_or_default();
+    // Bring the pages into the staging directory, reporting progress as files land
+    let details = fill(staged.path(), &mut tracker)?;
+    let title = details.title.unwrap_or(manga_input.title);
+
+    // The same pages count as a duplicate however they were packaged
+    let content_hash = match &details.stored_archive {
+        Some(file_name) => {
+            let mut archive = StoredArchive::open(&staged.path().join(file_name), None)?;
+            hashes::hash_archive_pages(&mut archive, &tracker)?
+        }
+        None => hashes::hash_pages(staged.path(), &tracker)?,
+    };
+    tracker.check_cancelled()?;
+    let skipped = tracker.into_skipped();
+    if duplicate_of.is_none() {
+        duplicate_of = check_duplicate(
+            manga_input.on_duplicate,
+            find_content_duplicate(&database, content_hash.as_deref())?,
+        )?;
+    }
+
+    // Move the finished import into place; it is still removed if registering fails
+    let manga_dir = library_path.join(&id);
+    staged.move_to(&manga_dir)?;
+
+    // Every directory holding pages becomes a chapter, and the cover comes from the first one
+    let (chapters, cover, archive_path) = match &details.stored_archive {
+        Some(file_name) => {
+            let archive_path = manga_dir.join(file_name);
+            let mut archive = StoredArchive::open(&archive_path, None)?;
+            let chapters = chapters_from_image_dirs(stored_archive::image_dirs(&archive), &title);
+            let cover = match chapters.first() {
+                Some(chapter) => stored_archive::extract_cover(
+                    &mut archive,
+                    &chapter.path,
+                    &manga_dir,
+                    ".cover",
+                )?,
+                None => None,
+            };
+            let archive_path = archive_path
+                .to_str()
+                .ok_or("Failed to convert path to string")?
+                .to_string();
+            (chapters, cover.unwrap_or_default(), Some(archive_path))
+        }
+        None => {
+            // Pages were sniffed while they were copied, so nothing is left to skip
+            let (chapters, _) = scan_chapters(&manga_dir, &title)?;
+            let cover = find_chapters_cover(&manga_dir, &chapters).unwrap_or_default();
+            (chapters, cover, None)
+        }
+    };
 
     // Create manga struct
     let manga = Manga {
         id: id.clone(),
-        title: manga_input.title,
+        title,
         path: manga_dir
             .to_str()
             .ok_or("Failed to convert path to string")?
@@ -54,152 +397,855 @@ pub fn import_manga_folder<R: Runtime>(
         created_at: chrono::Utc::now().to_string(),
         progress: 0,
         source: "local".to_string(),
+        chapters,
+        metadata: details.metadata,
+        linked: false,
+        source_missing: false,
+        archive: archive_path,
     };
 
-    // Save manga to library.json
-    add_manga_to_library(&app, &manga).map_err(|err| err.to_string())?;
+    // Save the manga together with its hashes
+    add_manga_to_library(&app, &manga, archive_hash, content_hash)?;
+    staged.keep();
+    generate_cover_thumbnails(&app_data_dir, &manga);
 
-    Ok(manga)
+    Ok(ImportResult {
+        manga,
+        duplicate_of,
+        skipped,
+    })
 }
 
-pub async fn import_manga_cbz<R: Runtime>(
+// Thumbnails are regenerated on demand, so a cover that can't be decoded doesn't fail the import
+pub fn generate_cover_thumbnails(app_data_dir: &Path, manga: &Manga) {
+    if manga.cover.is_empty() {
+        return;
+    }
+    if let Err(err) = thumbnails::generate_thumbnails(app_data_dir, &manga.id, &manga.cover) {
+        println!("Failed to generate thumbnails for {}: {}", manga.id, err);
+    }
+}
+
+// Fail the import on a duplicate when the policy says so
+fn check_duplicate(
+    policy: DuplicatePolicy,
+    duplicate: Option<DuplicateMatch>,
+) -> Result<Option<DuplicateMatch>, String> {
+    match duplicate {
+        Some(existing) if policy == DuplicatePolicy::Refuse => Err(format!(
+            "Already in the library as \"{}\" (ID {})",
+            existing.title, existing.id
+        )),
+        duplicate => Ok(duplicate),
+    }
+}
+
+// Imports without pages have no content hash and never match another manga
+fn find_content_duplicate(
+    database: &Database,
+    content_hash: Option<&str>,
+) -> Result<Option<DuplicateMatch>, String> {
+    match content_hash {
+        Some(hash) => database.find_by_content_hash(hash),
+        None => Ok(None),
+    }
+}
+
+// Directory of an import in progress, deleted on drop unless the import completed
+struct StagedDir {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl StagedDir {
+    fn create(path: PathBuf) -> Result<StagedDir, String> {
+        fs::create_dir_all(&path)
+            .map_err(|err| format!("Failed to create staging directory: {}", err))?;
+        Ok(StagedDir { path, keep: false })
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Rename into the library; staging shares the app data volume, so this is atomic
+    fn move_to(&mut self, destination: &Path) -> Result<(), String> {
+        fs::rename(&self.path, destination)
+            .map_err(|err| format!("Failed to move import into the library: {}", err))?;
+        self.path = destination.to_path_buf();
+        Ok(())
+    }
+
+    fn keep(&mut self) {
+        self.keep = true;
+    }
+}
+
+impl Drop for StagedDir {
+    fn drop(&mut self) {
+        if self.keep {
+            return;
+        }
+        if let Err(err) = fs::remove_dir_all(&self.path) {
+            println!("Failed to clean up {}: {}", self.path.display(), err);
+        }
+    }
+}
+
+// Register a folder where it is, without copying its pages into the library
+fn link_manga_folder<R: Runtime>(
     app: AppHandle<R>,
     manga_input: MangaInput,
-) -> Result<Manga, String> {
-    // Generate a unique ID for the manga
-    let id = nanoid!();
+) -> Result<ImportResult, String> {
+    categories::ensure_category(&app, &manga_input.category)?;
 
-    // Get app data directory
-    let app_data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
+    let source_folder = PathBuf::from(&manga_input.path);
+    if !source_folder.is_dir() {
+        return Err(format!("Folder not found: {}", manga_input.path));
+    }
 
-    // Create library directory if it doesn't exist
-    let library_path = app_data_dir.join("library");
-    fs::create_dir_all(&library_path).map_err(|err| err.to_string())?;
+    // Hashing a large folder takes a while, so the link can be cancelled like an import
+    let id = nanoid!();
+    let import_id = manga_input.import_id.clone().unwrap_or_else(|| id.clone());
+    let registry = app.state::<ImportRegistry>();
+    let running = registry.start(&import_id);
+    let tracker = ImportTracker::new(&app, &import_id, running.cancelled());
 
-    // Create manga-specific directory
-    let manga_dir = library_path.join(&id);
-    fs::create_dir_all(&manga_dir).map_err(|err| err.to_string())?;
+    // A folder whose pages are already in the library is reported like a copied one
+    let app_data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
+    let content_hash = hashes::hash_pages(&source_folder, &tracker)?;
+    let duplicate_of = check_duplicate(
+        manga_input.on_duplicate,
+        find_content_duplicate(&app.state::<Database>(), content_hash.as_deref())?,
+    )?;
 
-    // Extract CBZ file to the manga directory
-    extract_cbz_file(&manga_input.path, &manga_dir).map_err(|err| err.to_string())?;
+    // Chapters and cover are detected in place
+    let (chapters, skipped) = scan_chapters(&source_folder, &manga_input.title)?;
+    let cover = find_chapters_cover(&source_folder, &chapters).unwrap_or_default();
 
-    // Find suitable cover image
-    let cover = find_cover_image(&manga_dir).unwrap_or_default();
+    tracker.check_cancelled()?;
 
-    // Create manga struct
     let manga = Manga {
-        id: id.clone(),
+        id,
         title: manga_input.title,
-        path: manga_dir
-            .to_str()
-            .ok_or("Failed to convert path to string")?
-            .to_string(),
+        path: manga_input.path,
         category: manga_input.category,
         cover,
         last_read: None,
         created_at: chrono::Utc::now().to_string(),
         progress: 0,
         source: "local".to_string(),
+        chapters,
+        metadata: MangaMetadata::default(),
+        linked: true,
+        source_missing: false,
+        archive: None,
     };
 
-    add_manga_to_library(&app, &manga).map_err(|err| err.to_string())?;
+    add_manga_to_library(&app, &manga, None, content_hash)?;
+    generate_cover_thumbnails(&app_data_dir, &manga);
 
-    Ok(manga)
+    Ok(ImportResult {
+        manga,
+        duplicate_of,
+        skipped,
+    })
 }
 
-// Helper function to add manga to library.json
-fn add_manga_to_library<R: Runtime>(app: &AppHandle<R>, manga: &Manga) -> Result<(), String> {
+// Register a CBZ or CBR where it is; pages are read out of it like from a stored CBZ
+fn link_manga_archive<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: MangaInput,
+) -> Result<ImportResult, String> {
+    categories::ensure_category(&app, &manga_input.category)?;
+
+    let archive_path = PathBuf::from(&manga_input.path);
+    if !archive_path.is_file() {
+        return Err(format!("Archive not found: {}", manga_input.path));
+    }
+
+    // Unpacking and hashing a large archive takes a while, so the link can be cancelled
+    let id = nanoid!();
+    let import_id = manga_input.import_id.clone().unwrap_or_else(|| id.clone());
+    let registry = app.state::<ImportRegistry>();
+    let running = registry.start(&import_id);
+    let tracker = ImportTracker::new(&app, &import_id, running.cancelled());
+
+    // CBR pages are unpacked under app data; the copy is dropped again if linking fails
     let app_data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
-    let library_file = app_data_dir.join("library.json");
-
-    // Read existing library or create a new one
-    let mut library: Library = if library_file.exists() {
-        let content = fs::read_to_string(&library_file).map_err(|err| err.to_string())?;
-        serde_json::from_str(&content).unwrap_or_else(|_| Library { manga: Vec::new() })
-    } else {
-        Library { manga: Vec::new() }
+    let mut unpacked = StagedDir::create(stored_archive::unpack_dir(&app_data_dir, &id))?;
+    let mut archive = StoredArchive::open(&archive_path, Some(unpacked.path()))?;
+
+    // The same file, or the same pages packaged differently, is reported like an import
+    let database = app.state::<Database>();
+    let archive_hash = hashes::hash_file(&archive_path, &tracker)?;
+    let content_hash = hashes::hash_archive_pages(&mut archive, &tracker)?;
+    let duplicate = match database.find_by_archive_hash(&archive_hash)? {
+        Some(duplicate) => Some(duplicate),
+        None => find_content_duplicate(&database, content_hash.as_deref())?,
     };
+    let duplicate_of = check_duplicate(manga_input.on_duplicate, duplicate)?;
 
-    // Add new manga
-    library.manga.push(manga.clone());
+    // ComicInfo.xml only fills in the title when the user left it empty
+    let comic_info = stored_archive::read_comic_info(&mut archive);
+    let title = comic_info
+        .as_ref()
+        .and_then(ComicInfo::display_title)
+        .filter(|_| manga_input.title.trim().is_empty())
+        .unwrap_or(manga_input.title);
 
-    // Write back to file
-    let json = serde_json::to_string_pretty(&library).map_err(|err| err.to_string())?;
-    fs::write(library_file, json).map_err(|err| err.to_string())?;
+    // The cover is kept under app data, since the archive's folder belongs to the user
+    let chapters = chapters_from_image_dirs(stored_archive::image_dirs(&archive), &title);
+    let cover = match chapters.first() {
+        Some(chapter) => stored_archive::extract_cover(
+            &mut archive,
+            &chapter.path,
+            &app_data_dir.join("covers"),
+            &id,
+        )?,
+        None => None,
+    };
+    tracker.check_cancelled()?;
 
-    Ok(())
+    let manga = Manga {
+        id,
+        title,
+        path: manga_input.path.clone(),
+        category: manga_input.category,
+        cover: cover.unwrap_or_default(),
+        last_read: None,
+        created_at: chrono::Utc::now().to_string(),
+        progress: 0,
+        source: "local".to_string(),
+        chapters,
+        metadata: comic_info.map(|comic_info| comic_info.metadata).unwrap_or_default(),
+        linked: true,
+        source_missing: false,
+        archive: Some(manga_input.path),
+    };
+
+    add_manga_to_library(&app, &manga, Some(archive_hash), content_hash)?;
+    unpacked.keep();
+    generate_cover_thumbnails(&app_data_dir, &manga);
+
+    let skipped = archive
+        .skipped_entries()
+        .iter()
+        .map(|entry| SkippedFile {
+            path: entry.clone(),
+            reason: "not a recognised image format".to_string(),
+        })
+        .collect();
+    Ok(ImportResult {
+        manga,
+        duplicate_of,
+        skipped,
+    })
 }
 
-// Extract CBZ file to a directory
-fn extract_cbz_file(cbz_path: &str, destination: &Path) -> Result<(), String> {
-    // Open the CBZ file
-    let file = fs::File::open(cbz_path).map_err(|e| format!("Failed to open CBZ file: {}", e))?;
+// Ask a running import to stop; it cleans up after itself
+pub fn cancel_import<R: Runtime>(app: AppHandle<R>, import_id: &str) -> Result<(), String> {
+    if !app.state::<ImportRegistry>().cancel(import_id) {
+        return Err(format!("No running import with ID {}", import_id));
+    }
+    Ok(())
+}
 
-    // Create a ZipArchive from the file
-    let mut archive =
-        ZipArchive::new(file).map_err(|e| format!("Failed to read CBZ as ZIP archive: {}", e))?;
+// Pages inside PDFs, EPUBs and series of volumes can only be read once extracted
+fn reject_link(manga_input: &MangaInput) -> Result<(), String> {
+    if manga_input.link {
+        return Err(
+            "Only folders, CBZ and CBR files can be linked in place; this has to be imported"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
 
-    // Extract each file from the archive
-    for i in 0..archive.len() {
-        let mut file = archive
-            .by_index(i)
-            .map_err(|e| format!("Failed to access file in archive: {}", e))?;
+// Look up a manga before it is opened, re-checking that a linked source still exists
+pub fn open_manga<R: Runtime>(app: AppHandle<R>, manga_id: &str) -> Result<Manga, String> {
+    let mut manga = find_manga(&app, manga_id)?;
 
-        // Get the file name
-        let file_name = match file.enclosed_name() {
-            Some(path) => path.to_owned(),
-            None => continue, // Skip files with unsafe names
+    if manga.linked {
+        let source_missing = match &manga.archive {
+            Some(archive_path) => !Path::new(archive_path).is_file(),
+            None => !Path::new(&manga.path).is_dir(),
         };
 
-        // Skip directories and non-image files
-        if file.is_dir() {
-            continue;
+        // Only save the manga when the state actually changed
+        if source_missing != manga.source_missing {
+            manga.source_missing = source_missing;
+            save_manga(&app, &manga)?;
         }
+    }
 
-        // Check if it's an image file
-        let file_name_lower = file_name.to_string_lossy().to_lowercase();
-        if !file_name_lower.ends_with(".jpg")
-            && !file_name_lower.ends_with(".jpeg")
-            && !file_name_lower.ends_with(".png")
-            && !file_name_lower.ends_with(".webp")
-        {
-            continue;
-        }
+    Ok(manga)
+}
 
-        // Create the destination path
-        let dest_path = destination.join(file_name.file_name().unwrap());
+// Path of a resized cover for the library grid, generated on demand
+pub fn get_manga_thumbnail<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    size: ThumbnailSize,
+) -> Result<String, String> {
+    let manga = find_manga(&app, manga_id)?;
+    if manga.cover.is_empty() {
+        return Err(format!("Manga with ID {} has no cover", manga_id));
+    }
 
-        // Create a file to write to
-        let mut outfile = fs::File::create(&dest_path)
-            .map_err(|e| format!("Failed to create output file: {}", e))?;
+    let app_data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
+    thumbnails::get_thumbnail(&app_data_dir, &manga.id, &manga.cover, size)
+}
 
-        // Copy the file data
-        std::io::copy(&mut file, &mut outfile)
-            .map_err(|e| format!("Failed to write file data: {}", e))?;
+// List a chapter's pages in reading order
+pub fn list_pages<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    chapter_id: &str,
+) -> Result<PageList, String> {
+    let manga = open_manga(app.clone(), manga_id)?;
+    if manga.source_missing {
+        return Err(format!("Source folder is missing: {}", manga.path));
     }
 
+    // Manga imported before chapter detection have no chapters and keep pages at the root
+    let chapter = manga.chapters.iter().find(|chapter| chapter.id == chapter_id);
+    let (chapter_path, page_order) = match chapter {
+        Some(chapter) => (chapter.path.as_str(), chapter.page_order.as_slice()),
+        None if manga.chapters.is_empty() => ("", &[][..]),
+        None => return Err(format!("Chapter with ID {} not found", chapter_id)),
+    };
+
+    // Archive-backed pages are addressed as "<manga id>/<entry name>" on the page protocol
+    if manga.archive.is_some() {
+        let pages = stored_archive::list_chapter_pages(&app, &manga.id, chapter_path)?;
+        return Ok(PageList {
+            base_url: if chapter_path.is_empty() {
+                manga.id.clone()
+            } else {
+                format!("{}/{}", manga.id, chapter_path)
+            },
+            pages: order_pages(pages, page_order),
+            protocol: stored_archive::PAGE_PROTOCOL.to_string(),
+        });
+    }
+
+    let chapter_dir = Path::new(&manga.path).join(chapter_path);
+    let pages = order_pages(list_page_files(&chapter_dir)?, page_order);
+
+    Ok(PageList {
+        base_url: chapter_dir
+            .to_str()
+            .ok_or("Failed to convert path to string")?
+            .to_string(),
+        pages,
+        protocol: "asset".to_string(),
+    })
+}
+
+// Store a manual page order for a chapter; an empty order restores natural sorting
+pub fn set_page_order<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    chapter_id: &str,
+    pages: Vec<String>,
+) -> Result<(), String> {
+    let mut manga = find_manga(&app, manga_id)?;
+    let chapter = manga
+        .chapters
+        .iter_mut()
+        .find(|chapter| chapter.id == chapter_id)
+        .ok_or_else(|| format!("Chapter with ID {} not found", chapter_id))?;
+
+    chapter.page_order = pages;
+    save_manga(&app, &manga)
+}
+
+// The whole library, for the library grid
+pub fn get_library<R: Runtime>(app: AppHandle<R>) -> Result<Library, String> {
+    app.state::<Database>().library()
+}
+
+// Search, filter and sort the library in the database instead of over the whole list
+pub fn query_library<R: Runtime>(
+    app: AppHandle<R>,
+    query: LibraryQuery,
+) -> Result<LibraryPage, String> {
+    app.state::<Database>().query_library(&query)
+}
+
+pub fn get_manga<R: Runtime>(app: AppHandle<R>, manga_id: &str) -> Result<Manga, String> {
+    find_manga(&app, manga_id)
+}
+
+// Add a manga from an extension; one already in the library just moves to the category
+pub fn add_manga<R: Runtime>(
+    app: AppHandle<R>,
+    manga_input: OnlineMangaInput,
+) -> Result<Manga, String> {
+    categories::ensure_category(&app, &manga_input.category)?;
+
+    let existing = app.state::<Database>().library()?.manga.into_iter().find(|manga| {
+        manga_input.id.as_ref() == Some(&manga.id)
+            || (manga.source == manga_input.source_id && manga.title == manga_input.title)
+    });
+    if let Some(mut manga) = existing {
+        manga.category = manga_input.category;
+        save_manga(&app, &manga)?;
+        return Ok(manga);
+    }
+
+    let manga = Manga {
+        id: manga_input.id.unwrap_or_else(|| nanoid!()),
+        title: manga_input.title,
+        path: manga_input.path,
+        category: manga_input.category,
+        cover: manga_input.cover,
+        last_read: None,
+        created_at: chrono::Utc::now().to_string(),
+        progress: 0,
+        source: manga_input.source_id,
+        chapters: Vec::new(),
+        metadata: MangaMetadata {
+            description: manga_input.description.filter(|description| !description.is_empty()),
+            ..MangaMetadata::default()
+        },
+        linked: false,
+        source_missing: false,
+        archive: None,
+    };
+    save_manga(&app, &manga)?;
+    Ok(manga)
+}
+
+// Change a manga's title, category, cover or metadata
+pub fn update_manga<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    changes: MangaUpdate,
+) -> Result<Manga, String> {
+    let mut manga = find_manga(&app, manga_id)?;
+
+    if let Some(title) = changes.title {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err("Title cannot be empty".to_string());
+        }
+        manga.title = title.to_string();
+    }
+    if let Some(category) = changes.category {
+        categories::ensure_category(&app, &category)?;
+        manga.category = category;
+    }
+    if let Some(metadata) = changes.metadata {
+        apply_metadata_update(&mut manga.metadata, metadata)?;
+    }
+
+    // A new cover is an image file checked and copied into place like `set_manga_cover` does,
+    // which also replaces its thumbnails
+    if let Some(path) = changes.cover.filter(|path| *path != manga.cover) {
+        let source = CoverSource::File { path };
+        manga.cover = covers::set_manga_cover(app.clone(), manga_id, source)?.cover;
+    }
+    save_manga(&app, &manga)?;
+
+    Ok(manga)
+}
+
+// Edit a manga's metadata field by field, tidying up what the user typed
+pub fn update_manga_metadata<R: Runtime>(
+    app: AppHandle<R>,
+    manga_id: &str,
+    changes: MetadataUpdate,
+) -> Result<Manga, String> {
+    let mut manga = find_manga(&app, manga_id)?;
+    apply_metadata_update(&mut manga.metadata, changes)?;
+
+    save_manga(&app, &manga)?;
+    Ok(manga)
+}
+
+// The one place metadata edits are validated, whichever command they come through
+fn apply_metadata_update(
+    metadata: &mut MangaMetadata,
+    changes: MetadataUpdate,
+) -> Result<(), String> {
+    if let Some(reading_direction) = changes.reading_direction {
+        metadata.reading_direction = clean_text(reading_direction);
+        if let Some(direction) = &metadata.reading_direction {
+            if direction != "left-to-right" && direction != "right-to-left" {
+                return Err(format!("Unknown reading direction: {}", direction));
+            }
+        }
+    }
+    if let Some(authors) = changes.authors {
+        metadata.authors = clean_list(authors);
+    }
+    if let Some(artists) = changes.artists {
+        metadata.artists = clean_list(artists);
+    }
+    if let Some(description) = changes.description {
+        metadata.description = clean_text(description);
+    }
+    if let Some(genres) = changes.genres {
+        metadata.genres = clean_list(genres);
+    }
+    if let Some(tags) = changes.tags {
+        metadata.tags = clean_list(tags);
+    }
+    if let Some(status) = changes.status {
+        metadata.status = status;
+    }
+    if let Some(alternative_titles) = changes.alternative_titles {
+        metadata.alternative_titles = clean_list(alternative_titles);
+    }
+    if let Some(language) = changes.language {
+        metadata.language = clean_text(language);
+    }
+    // Site names are matched without regard to case; an empty ID removes the site
+    if let Some(external_ids) = changes.external_ids {
+        metadata.external_ids = external_ids
+            .into_iter()
+            .filter_map(|(site, id)| Some((clean_text(site)?.to_lowercase(), clean_text(id)?)))
+            .collect();
+    }
     Ok(())
 }
 
-// Helper function to copy folder contents
-fn copy_folder_contents(source: &Path, destination: &Path) -> Result<(), std::io::Error> {
-    for entry in fs::read_dir(source)? {
-        let entry = entry?;
-        let file_type = entry.file_type()?;
+// Trimmed text, or nothing when it is empty
+fn clean_text(text: String) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+// Trimmed, non-empty entries, keeping the first of any that differ only in case
+fn clean_list(items: Vec<String>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for item in items.into_iter().filter_map(clean_text) {
+        if !cleaned.iter().any(|existing| existing.to_lowercase() == item.to_lowercase()) {
+            cleaned.push(item);
+        }
+    }
+    cleaned
+}
+
+// Helper function to add a new manga and the hashes used to spot it being imported again
+fn add_manga_to_library<R: Runtime>(
+    app: &AppHandle<R>,
+    manga: &Manga,
+    archive_hash: Option<String>,
+    content_hash: Option<String>,
+) -> Result<(), String> {
+    // An empty content hash is stored for imports without pages; it is never looked up
+    let hashes = HashEntry {
+        manga_id: manga.id.clone(),
+        archive_hash,
+        content_hash: content_hash.unwrap_or_default(),
+    };
+    app.state::<Database>().add_manga(manga, &hashes)
+}
+
+// Helper function to look up one manga by ID
+pub fn find_manga<R: Runtime>(app: &AppHandle<R>, manga_id: &str) -> Result<Manga, String> {
+    app.state::<Database>()
+        .manga(manga_id)?
+        .ok_or_else(|| format!("Manga with ID {} not found", manga_id))
+}
+
+// Helper function to store changes to a manga and its chapters
+pub fn save_manga<R: Runtime>(app: &AppHandle<R>, manga: &Manga) -> Result<(), String> {
+    app.state::<Database>().save_manga(manga)
+}
+
+// Helper function to copy folder contents, recursing into subdirectories
+fn copy_folder_contents(
+    source: &Path,
+    destination: &Path,
+    tracker: &mut ImportTracker,
+) -> Result<(), String> {
+    // Sorted so name collisions always resolve the same way
+    let mut entries = fs::read_dir(source)
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        tracker.check_cancelled()?;
+
+        let file_type = entry.file_type().map_err(|err| err.to_string())?;
         let source_path = entry.path();
         let file_name = source_path.file_name().unwrap();
         let destination_path = destination.join(file_name);
 
-        if file_type.is_file() {
-            fs::copy(&source_path, &destination_path)?;
+        if file_type.is_dir() {
+            fs::create_dir_all(&destination_path).map_err(|err| err.to_string())?;
+            copy_folder_contents(&source_path, &destination_path, tracker)?;
+        } else if file_type.is_file() {
+            // Only page images are copied, named after their actual format
+            let mut file = fs::File::open(&source_path).map_err(|err| err.to_string())?;
+            let header = images::read_header(&mut file).map_err(|err| err.to_string())?;
+            let format = match ImageFormat::sniff(&header) {
+                Some(format) => format,
+                None => {
+                    tracker.file_skipped(
+                        &source_path.to_string_lossy(),
+                        "not a recognised image format",
+                    );
+                    continue;
+                }
+            };
+
+            // Renaming by format can make "001" and "001.png" collide
+            let destination_path =
+                archive::unique_path(&destination.join(format.file_name(Path::new(file_name))));
+            let bytes = fs::copy(&source_path, &destination_path).map_err(|err| err.to_string())?;
+            tracker.file_written(&source_path.to_string_lossy(), bytes);
         }
     }
     Ok(())
 }
 
+// Walk the manga directory and turn every leaf directory of images into a chapter.
+// A flat manga yields a single chapter rooted at the manga directory itself. Files that
+// aren't images are returned alongside, to be reported as skipped
+fn scan_chapters(
+    manga_dir: &Path,
+    manga_title: &str,
+) -> Result<(Vec<Chapter>, Vec<SkippedFile>), String> {
+    let mut image_dirs = Vec::new();
+    let mut skipped = Vec::new();
+    collect_image_dirs(manga_dir, manga_dir, &mut image_dirs, &mut skipped)
+        .map_err(|err| format!("Failed to scan manga directory: {}", err))?;
+
+    Ok((chapters_from_image_dirs(image_dirs, manga_title), skipped))
+}
+
+// Turn (relative directory, page count) pairs into chapters in reading order
+fn chapters_from_image_dirs(image_dirs: Vec<(String, u32)>, manga_title: &str) -> Vec<Chapter> {
+    let mut chapters: Vec<Chapter> = image_dirs
+        .into_iter()
+        .map(|(relative_path, page_count)| {
+            let title = relative_path
+                .rsplit('/')
+                .next()
+                .filter(|name| !name.is_empty())
+                .unwrap_or(manga_title)
+                .to_string();
+            let (volume, number) = parse_chapter_numbers(&relative_path);
+
+            Chapter {
+                id: nanoid!(),
+                title,
+                number,
+                volume,
+                page_count,
+                path: relative_path,
+                page_order: Vec::new(),
+            }
+        })
+        .collect();
+
+    chapters.sort_by(|a, b| {
+        compare_optional_numbers(a.volume, b.volume)
+            .then_with(|| compare_optional_numbers(a.number, b.number))
+            .then_with(|| natural_cmp(&a.path, &b.path))
+    });
+
+    chapters
+}
+
+// Helper function to collect (relative path, page count) for every leaf image directory.
+// Returns whether `dir` or any of its subdirectories holds images. Pages are recognised by
+// their content; hidden files such as stored covers are ignored
+fn collect_image_dirs(
+    root: &Path,
+    dir: &Path,
+    image_dirs: &mut Vec<(String, u32)>,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<bool, std::io::Error> {
+    let mut pages = Vec::new();
+    let mut has_image_subdirs = false;
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        if file_type.is_dir() {
+            has_image_subdirs |= collect_image_dirs(root, &path, image_dirs, skipped)?;
+        } else if file_type.is_file() {
+            if images::sniff_file(&path)?.is_some() {
+                pages.push(path);
+            } else {
+                skipped.push(SkippedFile {
+                    path: path.to_string_lossy().to_string(),
+                    reason: "not a recognised image format".to_string(),
+                });
+            }
+        }
+    }
+
+    let page_count = pages.len() as u32;
+    if !is_chapter_dir(page_count, has_image_subdirs) {
+        for page in &pages {
+            skipped.push(SkippedFile {
+                path: page.to_string_lossy().to_string(),
+                reason: "single image next to chapter folders, taken as a cover".to_string(),
+            });
+        }
+    } else if page_count > 0 {
+        let relative_path = dir
+            .strip_prefix(root)
+            .unwrap_or(dir)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        image_dirs.push((relative_path, page_count));
+    }
+
+    Ok(page_count > 0 || has_image_subdirs)
+}
+
+// Whether a directory's own pages make a chapter. A single image next to chapter folders is a
+// series cover, but more pages there (e.g. "Vol 01/*.jpg" beside "Vol 01/extras") are read too
+pub fn is_chapter_dir(page_count: u32, has_image_subdirs: bool) -> bool {
+    page_count > 1 || (page_count == 1 && !has_image_subdirs)
+}
+
+// Extract (volume, chapter number) from a relative chapter path such as "Vol 01/Ch 001"
+fn parse_chapter_numbers(relative_path: &str) -> (Option<f32>, Option<f32>) {
+    let mut volume = None;
+    let mut number = None;
+
+    for component in relative_path.split('/') {
+        let name = component.to_lowercase();
+        if let Some(value) = find_marked_number(&name, &["volume", "vol", "v"]) {
+            volume = Some(value);
+        }
+        if let Some(value) = find_marked_number(&name, &["chapter", "ch", "c", "#"]) {
+            number = Some(value);
+        }
+    }
+
+    // Fall back to the first number in the leaf name, e.g. "001" or "Episode 12"
+    if number.is_none() {
+        let leaf = relative_path.rsplit('/').next().unwrap_or_default().to_lowercase();
+        if find_marked_number(&leaf, &["volume", "vol", "v"]).is_none() {
+            number = leading_number(leaf.trim_start_matches(|c: char| !c.is_ascii_digit()));
+        }
+    }
+
+    (volume, number)
+}
+
+// Find a number directly following one of the given markers, e.g. "vol.02" or "ch 3.5"
+fn find_marked_number(name: &str, markers: &[&str]) -> Option<f32> {
+    for marker in markers {
+        for (index, _) in name.match_indices(marker) {
+            // Markers must start a word so that "ch" in "watch" doesn't count
+            let starts_word = name[..index]
+                .chars()
+                .next_back()
+                .is_none_or(|c| !c.is_alphanumeric());
+            if !starts_word {
+                continue;
+            }
+
+            let rest = name[index + marker.len()..]
+                .trim_start_matches(['.', ' ', '_', '-']);
+            if let Some(value) = leading_number(rest) {
+                return Some(value);
+            }
+        }
+    }
+    None
+}
+
+// Parse the number at the start of a string, allowing a fractional part ("10.5")
+fn leading_number(text: &str) -> Option<f32> {
+    let end = text
+        .char_indices()
+        .find(|&(index, c)| !(c.is_ascii_digit() || (c == '.' && index > 0)))
+        .map_or(text.len(), |(index, _)| index);
+    text[..end].trim_end_matches('.').parse().ok()
+}
+
+// Order optional numbers with missing values last
+fn compare_optional_numbers(a: Option<f32>, b: Option<f32>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+// Find a cover in the first chapter, falling back to the manga directory itself
+fn find_chapters_cover(manga_dir: &Path, chapters: &[Chapter]) -> Option<String> {
+    chapters
+        .first()
+        .and_then(|chapter| find_cover_image(&manga_dir.join(&chapter.path)))
+        .or_else(|| find_cover_image(manga_dir))
+}
+
+// Compare names the way people expect files to sort: digit runs by value, letters case-insensitively
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        let (a_char, b_char) = match (a_chars.peek(), b_chars.peek()) {
+            // Names that only differ in case or leading zeros still need a stable order
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(&a_char), Some(&b_char)) => (a_char, b_char),
+        };
+
+        let ordering = if a_char.is_ascii_digit() && b_char.is_ascii_digit() {
+            let a_digits = take_digits(&mut a_chars);
+            let b_digits = take_digits(&mut b_chars);
+            let a_value = a_digits.trim_start_matches('0');
+            let b_value = b_digits.trim_start_matches('0');
+            a_value.len().cmp(&b_value.len()).then_with(|| a_value.cmp(b_value))
+        } else {
+            a_chars.next();
+            b_chars.next();
+            a_char.to_lowercase().cmp(b_char.to_lowercase())
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+// Consume a run of ASCII digits
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        digits.push(c);
+        chars.next();
+    }
+    digits
+}
+
+// Extension check for file names only, such as covers; pages are sniffed by their content
+pub fn is_image_file(file_name: &str) -> bool {
+    ImageFormat::from_file_name(file_name).is_some()
+}
+
 // Function to find a suitable cover image in the manga directory
 fn find_cover_image(manga_dir: &Path) -> Option<String> {
-    let image_extensions = [".jpg", ".jpeg", ".png", ".webp"];
     let cover_keywords = ["cover", "front", "001", "page1", "page01", "0001"];
 
     // Try to find a file that looks like a cover
@@ -211,13 +1257,12 @@ fn find_cover_image(manga_dir: &Path) -> Option<String> {
                 if let Some(file_name) = path.file_name() {
                     let file_name_str = file_name.to_string_lossy().to_lowercase();
 
-                    // Check if filename contains cover keywords
+                    // Check if filename contains cover keywords; the content has to be an
+                    // image thumbnails can be made from
                     if cover_keywords
                         .iter()
                         .any(|keyword| file_name_str.contains(keyword))
-                        && image_extensions
-                        .iter()
-                        .any(|ext| file_name_str.ends_with(ext))
+                        && images::is_cover_file(&path)
                     {
                         return path.to_str().map(|s| s.to_string());
                     }
@@ -226,23 +1271,132 @@ fn find_cover_image(manga_dir: &Path) -> Option<String> {
         }
     }
 
-    // Second pass: just take the first image file
-    if let Ok(entries) = fs::read_dir(manga_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() {
-                if let Some(file_name) = path.file_name() {
-                    let file_name_str = file_name.to_string_lossy().to_lowercase();
+    // Second pass: just take the first page thumbnails can be made from
+    list_page_files(manga_dir)
+        .ok()?
+        .into_iter()
+        .map(|page| manga_dir.join(page))
+        .find(|path| images::is_cover_file(path))
+        .and_then(|path| path.to_str().map(|s| s.to_string()))
+}
 
-                    if image_extensions
-                        .iter()
-                        .any(|ext| file_name_str.ends_with(ext))
-                    {
-                        return path.to_str().map(|s| s.to_string());
-                    }
-                }
-            }
+// Image file names directly inside a directory, in natural order; hidden files are skipped.
+// Linked folders may hold misnamed pages, so the content decides what is a page
+pub fn list_page_files(dir: &Path) -> Result<Vec<String>, String> {
+    let mut pages: Vec<String> = fs::read_dir(dir)
+        .map_err(|err| format!("Failed to read pages: {}", err))?
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .filter(|entry| matches!(images::sniff_file(&entry.path()), Ok(Some(_))))
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+
+    pages.sort_by(|a, b| natural_cmp(a, b));
+    Ok(pages)
+}
+
+// Apply a manual order: listed pages that still exist come first, the rest keep their order
+pub fn order_pages(pages: Vec<String>, page_order: &[String]) -> Vec<String> {
+    let mut ordered: Vec<String> = Vec::new();
+    for page in page_order {
+        if pages.contains(page) && !ordered.contains(page) {
+            ordered.push(page.clone());
         }
     }
-    None
+
+    let remaining: Vec<String> = pages
+        .into_iter()
+        .filter(|page| !ordered.contains(page))
+        .collect();
+    ordered.extend(remaining);
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn natural_cmp_orders_digit_runs_by_value() {
+        assert_eq!(natural_cmp("v02_c010_p003.jpg", "v02_c9_p010.jpg"), Ordering::Greater);
+        assert_eq!(natural_cmp("page2.png", "page10.png"), Ordering::Less);
+        assert_eq!(natural_cmp("Page 1.png", "page 1.png"), Ordering::Less);
+        assert_eq!(natural_cmp("01.png", "1.png"), Ordering::Less);
+    }
+
+    #[test]
+    fn parses_volume_and_chapter_markers() {
+        assert_eq!(parse_chapter_numbers("v02_c010_p003.jpg"), (Some(2.0), Some(10.0)));
+        assert_eq!(parse_chapter_numbers("Vol 01/Ch 3.5"), (Some(1.0), Some(3.5)));
+        assert_eq!(parse_chapter_numbers("Watch 4"), (None, Some(4.0)));
+    }
+
+    #[test]
+    fn parses_names_without_markers() {
+        assert_eq!(parse_chapter_numbers("Episode 12"), (None, Some(12.0)));
+        assert_eq!(parse_chapter_numbers("Vol 03"), (Some(3.0), None));
+        assert_eq!(parse_chapter_numbers("Extras"), (None, None));
+    }
+
+    #[test]
+    fn order_pages_applies_override_first() {
+        let pages = ["001.jpg", "002.jpg", "003.jpg", "004.jpg"].map(String::from).to_vec();
+        let page_order = ["003.jpg", "missing.jpg", "001.jpg", "003.jpg"].map(String::from);
+
+        assert_eq!(
+            order_pages(pages.clone(), &page_order),
+            ["003.jpg", "001.jpg", "002.jpg", "004.jpg"]
+        );
+        assert_eq!(order_pages(pages.clone(), &[]), pages);
+    }
+
+    #[test]
+    fn pages_beside_a_chapter_folder_are_a_chapter_too() {
+        let root = std::env::temp_dir().join(format!("dokusho-chapters-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (name, data) in [
+            ("cover.jpg", &b"\xff\xd8\xffcover"[..]),
+            ("Vol 01/001.jpg", b"\xff\xd8\xffone"),
+            ("Vol 01/002.jpg", b"\xff\xd8\xfftwo"),
+            ("Vol 01/notes.txt", b"not a page"),
+            ("Vol 01/extras/001.png", b"\x89PNG\r\n\x1a\nextra"),
+        ] {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, data).unwrap();
+        }
+
+        let mut image_dirs = Vec::new();
+        let mut skipped = Vec::new();
+        collect_image_dirs(&root, &root, &mut image_dirs, &mut skipped).unwrap();
+        image_dirs.sort();
+        let mut skipped: Vec<String> = skipped
+            .into_iter()
+            .map(|file| file.path.replace(&*root.to_string_lossy(), "").replace('\\', "/"))
+            .collect();
+        skipped.sort();
+
+        assert_eq!(image_dirs, [("Vol 01".to_string(), 2), ("Vol 01/extras".to_string(), 1)]);
+        assert_eq!(skipped, ["/Vol 01/notes.txt", "/cover.jpg"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn cover_is_picked_by_content() {
+        let root = std::env::temp_dir().join(format!("dokusho-cover-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        for (name, data) in [
+            ("cover.jpg", &b"not an image"[..]),
+            ("001.avif", b"\0\0\0\x1cftypavif\0\0\0\0"),
+            ("002.jpg", b"\xff\xd8\xfftwo"),
+        ] {
+            fs::write(root.join(name), data).unwrap();
+        }
+
+        let cover = find_cover_image(&root).unwrap();
+        assert!(cover.ends_with("002.jpg"), "{}", cover);
+        fs::remove_dir_all(&root).unwrap();
+    }
 }
\ No newline at end of file
@@ -0,0 +1,419 @@
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, Runtime};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+use crate::library::archive::{self, enclosed_name, ArchiveFormat, EntryVisitor};
+use crate::library::comicinfo::{self, ComicInfo};
+use crate::library::images::{self, ImageFormat};
+use crate::library::service::{self, natural_cmp};
+use crate::library::util::{self, percent_decode};
+
+// URI scheme serving pages of archives read in place, e.g. manga-page://localhost/<manga id>/<entry>
+pub const PAGE_PROTOCOL: &str = "manga-page";
+
+// How many archives stay open between page requests
+const OPEN_ARCHIVE_LIMIT: usize = 4;
+
+// Filled in by the first request for the archive; the others wait for it instead of opening
+// the archive again
+type SharedArchive = Arc<Mutex<Option<StoredArchive>>>;
+
+// Recently read archives by manga id, most recent first, so turning a page doesn't
+// re-read the archive's directory
+#[derive(Default)]
+pub struct ArchiveCache {
+    archives: Mutex<VecDeque<(String, SharedArchive)>>,
+}
+
+impl ArchiveCache {
+    fn with_archive<R: Runtime, T>(
+        &self,
+        app: &AppHandle<R>,
+        manga_id: &str,
+        read: impl FnOnce(&mut StoredArchive) -> Result<T, String>,
+    ) -> Result<T, String> {
+        // The cache is only locked to find the archive, so opening one doesn't hold up the rest
+        let shared = {
+            let mut archives = self.archives.lock().unwrap();
+            let entry = match archives.iter().position(|(id, _)| id == manga_id) {
+                Some(index) => archives.remove(index).unwrap(),
+                None => (manga_id.to_string(), SharedArchive::default()),
+            };
+            let shared = entry.1.clone();
+            archives.push_front(entry);
+            archives.truncate(OPEN_ARCHIVE_LIMIT);
+            shared
+        };
+
+        let mut slot = shared.lock().unwrap();
+        let mut archive = match slot.take() {
+            Some(archive) => archive,
+            None => open_library_archive(app, manga_id)?,
+        };
+        let result = read(&mut archive);
+        *slot = Some(archive);
+        result
+    }
+
+    // Forget an archive before its file is deleted or replaced
+    pub fn evict(&self, manga_id: &str) {
+        self.archives.lock().unwrap().retain(|(id, _)| id != manga_id);
+    }
+}
+
+// Only archives registered in the library can be served
+fn open_library_archive<R: Runtime>(
+    app: &AppHandle<R>,
+    manga_id: &str,
+) -> Result<StoredArchive, String> {
+    let manga = service::open_manga(app.clone(), manga_id)?;
+    let archive_path = manga
+        .archive
+        .ok_or_else(|| format!("Manga with ID {} is not stored as an archive", manga_id))?;
+    let app_data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
+    StoredArchive::open(
+        Path::new(&archive_path),
+        Some(&unpack_dir(&app_data_dir, manga_id)),
+    )
+}
+
+// A CBZ or CBR whose pages are read one at a time instead of being extracted
+pub struct StoredArchive {
+    path: PathBuf,
+    reader: EntryReader,
+    // Page entry names in natural order
+    pages: Vec<String>,
+    // Entries that aren't images, which imports report as skipped
+    skipped: Vec<String>,
+    comic_info: Option<String>,
+}
+
+enum EntryReader {
+    Zip(ZipArchive<fs::File>),
+    // RAR has no central directory and solid archives can't be read from the middle, so CBR
+    // pages are unpacked once into this directory and read from there
+    Unpacked(PathBuf),
+}
+
+impl StoredArchive {
+    // `unpack_dir` is where a CBR's pages are kept; only CBZs can be opened without one
+    pub fn open(path: &Path, unpack_dir: Option<&Path>) -> Result<StoredArchive, String> {
+        let (reader, index) = match ArchiveFormat::detect(path)? {
+            ArchiveFormat::Zip => {
+                let file =
+                    fs::File::open(path).map_err(|e| format!("Failed to open CBZ file: {}", e))?;
+                let reader = EntryReader::Zip(
+                    ZipArchive::new(file)
+                        .map_err(|e| format!("Failed to read CBZ as ZIP archive: {}", e))?,
+                );
+                (reader, list_entries(path, None)?)
+            }
+            ArchiveFormat::Rar => {
+                let unpack_dir =
+                    unpack_dir.ok_or("CBR archives can only be read from an unpacked copy")?;
+                let index = unpack_rar(path, unpack_dir)?;
+                (EntryReader::Unpacked(unpack_dir.join("pages")), index)
+            }
+            _ => return Err("Only CBZ and CBR archives can be read without extracting".to_string()),
+        };
+
+        Ok(StoredArchive {
+            path: path.to_path_buf(),
+            reader,
+            pages: index.pages,
+            skipped: index.skipped,
+            comic_info: index.comic_info,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Page entry names, in natural order
+    pub fn page_entries(&self) -> &[String] {
+        &self.pages
+    }
+
+    pub fn skipped_entries(&self) -> &[String] {
+        &self.skipped
+    }
+
+    pub fn read_entry(&mut self, name: &str) -> Result<Vec<u8>, String> {
+        match &mut self.reader {
+            EntryReader::Zip(archive) => util::read_entry(archive, name),
+            EntryReader::Unpacked(dir) => {
+                // Names come from page requests, so only listed entries are read
+                let listed = self.pages.iter().any(|page| page == name)
+                    || self.comic_info.as_deref() == Some(name);
+                let path = enclosed_name(Path::new(name))
+                    .filter(|_| listed)
+                    .ok_or_else(|| format!("Missing {} in archive", name))?;
+                fs::read(dir.join(path))
+                    .map_err(|e| format!("Failed to read {} from archive: {}", name, e))
+            }
+        }
+    }
+}
+
+// Where a linked CBR's pages are unpacked to
+pub fn unpack_dir(app_data_dir: &Path, manga_id: &str) -> PathBuf {
+    app_data_dir.join("page-cache").join(manga_id)
+}
+
+// Drop the unpacked pages of a manga that left the library
+pub fn remove_unpacked(app_data_dir: &Path, manga_id: &str) {
+    let dir = unpack_dir(app_data_dir, manga_id);
+    if dir.exists() {
+        if let Err(err) = fs::remove_dir_all(&dir) {
+            println!("Failed to clean up {}: {}", dir.display(), err);
+        }
+    }
+}
+
+// Page listing of an archive; for unpacked CBRs it is saved next to the pages together with
+// the size and modification time of the CBR it was read from
+#[derive(Serialize, Deserialize, Default)]
+struct EntryIndex {
+    source_size: u64,
+    source_modified: u64,
+    pages: Vec<String>,
+    skipped: Vec<String>,
+    comic_info: Option<String>,
+}
+
+// Read an archive's entries in one pass, writing pages and ComicInfo.xml below `unpack_dir`
+// when given
+fn list_entries(path: &Path, unpack_dir: Option<&Path>) -> Result<EntryIndex, String> {
+    let mut lister = EntryLister {
+        unpack_dir: unpack_dir.map(Path::to_path_buf),
+        index: EntryIndex::default(),
+    };
+    archive::open_archive(path)?.for_each_file(&mut lister)?;
+    lister.index.pages.sort_by(|a, b| natural_cmp(a, b));
+    Ok(lister.index)
+}
+
+// Unpack a CBR unless an earlier copy of the same file is still there
+fn unpack_rar(path: &Path, unpack_dir: &Path) -> Result<EntryIndex, String> {
+    let metadata =
+        fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let source_modified = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |modified| modified.as_millis() as u64);
+
+    let index_path = unpack_dir.join("index.json");
+    let cached = fs::read_to_string(&index_path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<EntryIndex>(&json).ok())
+        .filter(|index| {
+            index.source_size == metadata.len() && index.source_modified == source_modified
+        });
+    if let Some(index) = cached {
+        return Ok(index);
+    }
+
+    let pages_dir = unpack_dir.join("pages");
+    if pages_dir.exists() {
+        fs::remove_dir_all(&pages_dir)
+            .map_err(|e| format!("Failed to clear unpacked pages: {}", e))?;
+    }
+    fs::create_dir_all(unpack_dir).map_err(|err| err.to_string())?;
+    let mut index = list_entries(path, Some(&pages_dir))?;
+    index.source_size = metadata.len();
+    index.source_modified = source_modified;
+
+    let json = serde_json::to_string(&index).map_err(|err| err.to_string())?;
+    fs::write(&index_path, json).map_err(|e| format!("Failed to write page index: {}", e))?;
+    Ok(index)
+}
+
+// Records the page and ComicInfo.xml entries of an archive; pages are recognised by their
+// content like extracted ones, so a misnamed or extensionless page still counts
+struct EntryLister {
+    unpack_dir: Option<PathBuf>,
+    index: EntryIndex,
+}
+
+impl EntryLister {
+    fn unpack(&self, name: &Path, header: &[u8], data: &mut dyn Read) -> Result<(), String> {
+        let (unpack_dir, path) = match (&self.unpack_dir, enclosed_name(name)) {
+            (Some(unpack_dir), Some(path)) => (unpack_dir, path),
+            _ => return Ok(()),
+        };
+
+        let path = unpack_dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let mut file = fs::File::create(&path)
+            .map_err(|e| format!("Failed to unpack {}: {}", name.display(), e))?;
+        file.write_all(header)
+            .and_then(|_| io::copy(data, &mut file))
+            .map_err(|e| format!("Failed to unpack {}: {}", name.display(), e))?;
+        Ok(())
+    }
+}
+
+impl EntryVisitor for EntryLister {
+    fn wants(&self, name: &Path) -> bool {
+        enclosed_name(name).is_some()
+    }
+
+    fn visit(&mut self, name: &Path, data: &mut dyn Read) -> Result<(), String> {
+        if comicinfo::is_comic_info(name) {
+            self.index.comic_info = Some(entry_path(name));
+            return self.unpack(name, &[], data);
+        }
+
+        let header = images::read_header(data)
+            .map_err(|e| format!("Failed to read file in archive: {}", e))?;
+        if ImageFormat::sniff(&header).is_some() {
+            self.index.pages.push(entry_path(name));
+            self.unpack(name, &header, data)
+        } else {
+            self.index.skipped.push(entry_path(name));
+            Ok(())
+        }
+    }
+}
+
+// Entry name with forward slashes, the way pages are addressed
+pub fn entry_path(name: &Path) -> String {
+    name.to_string_lossy().replace('\\', "/")
+}
+
+// Directories holding pages with their page counts, like `collect_image_dirs` on disk
+pub fn image_dirs(archive: &StoredArchive) -> Vec<(String, u32)> {
+    let mut page_counts: BTreeMap<String, u32> = BTreeMap::new();
+    for entry in archive.page_entries() {
+        *page_counts.entry(parent_dir(entry).to_string()).or_default() += 1;
+    }
+
+    let dirs: Vec<String> = page_counts.keys().cloned().collect();
+    page_counts
+        .into_iter()
+        .filter(|(dir, page_count)| {
+            let has_image_subdirs = dirs.iter().any(|other| {
+                other != dir && (dir.is_empty() || other.starts_with(&format!("{}/", dir)))
+            });
+            service::is_chapter_dir(*page_count, has_image_subdirs)
+        })
+        .collect()
+}
+
+// File names of the pages directly inside one directory of the archive, in natural order
+pub fn list_pages(archive: &StoredArchive, chapter_path: &str) -> Vec<String> {
+    archive
+        .page_entries()
+        .iter()
+        .filter(|entry| parent_dir(entry) == chapter_path)
+        .map(|entry| entry[entry.rfind('/').map_or(0, |index| index + 1)..].to_string())
+        .collect()
+}
+
+// The archive's ComicInfo.xml, ignoring broken metadata like extraction does
+pub fn read_comic_info(archive: &mut StoredArchive) -> Option<ComicInfo> {
+    let name = archive.comic_info.clone()?;
+    let xml = String::from_utf8(archive.read_entry(&name).ok()?).ok()?;
+    comicinfo::parse_comic_info(&xml).ok()
+}
+
+// Write the first page of a chapter to `<cover_dir>/<file_stem>.<ext>` as the cover
+pub fn extract_cover(
+    archive: &mut StoredArchive,
+    chapter_path: &str,
+    cover_dir: &Path,
+    file_stem: &str,
+) -> Result<Option<String>, String> {
+    // The first page thumbnails can be made from; its extension comes from the content
+    for page in list_pages(archive, chapter_path) {
+        let data = archive.read_entry(&entry_name(chapter_path, &page))?;
+        let format = match ImageFormat::sniff(&data) {
+            Some(format) if format.is_decodable() => format,
+            _ => continue,
+        };
+
+        fs::create_dir_all(cover_dir).map_err(|err| err.to_string())?;
+        let cover_path = cover_dir.join(format!("{}.{}", file_stem, format.extensions()[0]));
+        fs::write(&cover_path, data)
+            .map_err(|e| format!("Failed to write cover image: {}", e))?;
+        return Ok(cover_path.to_str().map(|s| s.to_string()));
+    }
+    Ok(None)
+}
+
+// Page names for a chapter of an archive-backed manga
+pub fn list_chapter_pages<R: Runtime>(
+    app: &AppHandle<R>,
+    manga_id: &str,
+    chapter_path: &str,
+) -> Result<Vec<String>, String> {
+    app.state::<ArchiveCache>().with_archive(app, manga_id, |archive| {
+        Ok(list_pages(archive, chapter_path))
+    })
+}
+
+pub fn read_chapter_page<R: Runtime>(
+    app: &AppHandle<R>,
+    manga_id: &str,
+    chapter_path: &str,
+    page: &str,
+) -> Result<Vec<u8>, String> {
+    app.state::<ArchiveCache>().with_archive(app, manga_id, |archive| {
+        archive.read_entry(&entry_name(chapter_path, page))
+    })
+}
+
+// Handle a request to the page protocol; the path is "<manga id>/<entry name>", percent-encoded
+pub fn serve_page<R: Runtime>(app: &AppHandle<R>, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let path = percent_decode(request.uri().path().trim_start_matches('/'));
+
+    let page = path
+        .split_once('/')
+        .ok_or_else(|| format!("Invalid page path: {}", path))
+        .and_then(|(manga_id, entry)| {
+            app.state::<ArchiveCache>()
+                .with_archive(app, manga_id, |archive| archive.read_entry(entry))
+        });
+
+    match page {
+        Ok(data) => Response::builder()
+            .header(header::CONTENT_TYPE, content_type(&path, &data))
+            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .body(data),
+        Err(err) => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header(header::CONTENT_TYPE, "text/plain")
+            .body(err.into_bytes()),
+    }
+    .unwrap_or_default()
+}
+
+// Archive entry name of a page inside a chapter directory
+pub fn entry_name(chapter_path: &str, page: &str) -> String {
+    if chapter_path.is_empty() {
+        page.to_string()
+    } else {
+        format!("{}/{}", chapter_path, page)
+    }
+}
+
+fn parent_dir(entry: &str) -> &str {
+    entry.rfind('/').map_or("", |index| &entry[..index])
+}
+
+// Trust the page's content over its name, like imports do
+fn content_type(name: &str, data: &[u8]) -> &'static str {
+    ImageFormat::sniff(data)
+        .or_else(|| ImageFormat::from_file_name(name))
+        .map_or("application/octet-stream", ImageFormat::content_type)
+}
@@ -0,0 +1,136 @@
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use image::codecs::jpeg::JpegEncoder;
+use image::ImageReader;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+const THUMBNAIL_QUALITY: u8 = 85;
+
+// Card sizes from the `manga_card_size` setting
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailSize {
+    Small,
+    // "default" is what settings.json holds until the user picks a size
+    #[serde(alias = "default")]
+    Medium,
+    Large,
+}
+
+impl ThumbnailSize {
+    pub const ALL: [ThumbnailSize; 3] =
+        [ThumbnailSize::Small, ThumbnailSize::Medium, ThumbnailSize::Large];
+
+    // Pixel width, twice the card width so covers stay sharp on high-DPI screens
+    fn width(self) -> u32 {
+        match self {
+            ThumbnailSize::Small => 320,
+            ThumbnailSize::Medium => 480,
+            ThumbnailSize::Large => 640,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ThumbnailSize::Small => "small",
+            ThumbnailSize::Medium => "medium",
+            ThumbnailSize::Large => "large",
+        }
+    }
+}
+
+fn thumbnail_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("thumbnails")
+}
+
+// Path of a cover thumbnail, generating it when it is missing or the cover changed since
+pub fn get_thumbnail(
+    app_data_dir: &Path,
+    manga_id: &str,
+    cover: &str,
+    size: ThumbnailSize,
+) -> Result<String, String> {
+    let dir = thumbnail_dir(app_data_dir);
+    let prefix = format!("{}-{}-", manga_id, size.name());
+
+    // The cover's path, size and modification time are part of the name, so a changed
+    // cover never matches an old thumbnail
+    let thumbnail_path = dir.join(format!("{}{}.jpg", prefix, cover_key(Path::new(cover))?));
+    if !thumbnail_path.exists() {
+        fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+        remove_thumbnails(&dir, &prefix);
+        write_thumbnail(Path::new(cover), &thumbnail_path, size.width())?;
+    }
+
+    thumbnail_path
+        .to_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| "Failed to convert path to string".to_string())
+}
+
+// Generate every size up front so the library grid never waits on a decode
+pub fn generate_thumbnails(app_data_dir: &Path, manga_id: &str, cover: &str) -> Result<(), String> {
+    for size in ThumbnailSize::ALL {
+        get_thumbnail(app_data_dir, manga_id, cover, size)?;
+    }
+    Ok(())
+}
+
+// Drop every cached thumbnail of a manga, e.g. after it was deleted
+pub fn invalidate_thumbnails(app_data_dir: &Path, manga_id: &str) {
+    remove_thumbnails(&thumbnail_dir(app_data_dir), &format!("{}-", manga_id));
+}
+
+fn remove_thumbnails(dir: &Path, prefix: &str) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with(prefix) {
+            if let Err(err) = fs::remove_file(entry.path()) {
+                println!("Failed to remove thumbnail {}: {}", entry.path().display(), err);
+            }
+        }
+    }
+}
+
+fn cover_key(cover: &Path) -> Result<String, String> {
+    let metadata = fs::metadata(cover).map_err(|e| format!("Cover image not found: {}", e))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|modified| modified.as_millis())
+        .unwrap_or_default();
+
+    let key = Sha256::digest(format!("{}|{}|{}", cover.display(), metadata.len(), modified));
+    Ok(format!("{:x}", key)[..16].to_string())
+}
+
+fn write_thumbnail(cover: &Path, destination: &Path, width: u32) -> Result<(), String> {
+    let image = ImageReader::open(cover)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|e| format!("Failed to open cover image: {}", e))?
+        .decode()
+        .map_err(|e| format!("Failed to decode cover image: {}", e))?;
+
+    // Small covers are kept at their own size rather than upscaled
+    let image = if image.width() > width {
+        image.thumbnail(width, u32::MAX)
+    } else {
+        image
+    };
+
+    // Encode next to the final path so a half-written thumbnail is never picked up
+    let temp_path = destination.with_extension("tmp");
+    let file = fs::File::create(&temp_path)
+        .map_err(|e| format!("Failed to create thumbnail file: {}", e))?;
+    JpegEncoder::new_with_quality(BufWriter::new(file), THUMBNAIL_QUALITY)
+        .encode_image(&image.to_rgb8())
+        .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+    fs::rename(&temp_path, destination).map_err(|e| format!("Failed to save thumbnail: {}", e))
+}
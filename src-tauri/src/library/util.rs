@@ -0,0 +1,41 @@
+use std::fs;
+use std::io::Read;
+use zip::ZipArchive;
+
+// Helpers shared by the importers and the page protocol
+
+// The whole contents of a ZIP entry, e.g. a page of a stored CBZ or a file inside an EPUB
+pub fn read_entry(archive: &mut ZipArchive<fs::File>, name: &str) -> Result<Vec<u8>, String> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|e| format!("Missing {} in archive: {}", name, e))?;
+    let mut data = Vec::new();
+    entry
+        .read_to_end(&mut data)
+        .map_err(|e| format!("Failed to read {} from archive: {}", name, e))?;
+    Ok(data)
+}
+
+// Decode %XX escapes as used in URLs and EPUB hrefs; malformed escapes are kept as they are
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            if let Some(byte) = text
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
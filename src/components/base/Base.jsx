@@ -6,6 +6,7 @@ import {SidebarInset, SidebarProvider} from "@/components/ui/sidebar.jsx";
 import AppBar from "@/components/base/AppBar.jsx";
 import {useAtom, useAtomValue} from "jotai";
 import {initializeLibraryAtom} from "@/store/library.js";
+import {initializeCategoriesAtom} from "@/store/categories.js";
 import {initializeSettingsAtom, settingsAtom} from "@/store/settings.js";
 import {focusAtom} from "jotai-optics";
 import {initializeExtensionsAtom} from "@/store/extensions.js";
@@ -15,10 +16,12 @@ const themeAtom = focusAtom(settingsAtom, optic => optic.prop("theme"))
 const Base = () => {
   const theme = useAtomValue(themeAtom);
   const [, initializeLibrary] = useAtom(initializeLibraryAtom)
+  const [, initializeCategories] = useAtom(initializeCategoriesAtom)
   const [, initializeSettings] = useAtom(initializeSettingsAtom)
   const [, initializeExtensions] = useAtom(initializeExtensionsAtom)
   useEffect(() => {
     initializeLibrary();
+    initializeCategories();
     initializeSettings();
     initializeExtensions();
   }, [])
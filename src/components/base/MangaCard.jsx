@@ -1,4 +1,4 @@
-import React from 'react';
+import React, {useEffect, useState} from 'react';
 import {useNavigate} from "react-router";
 import {
   BookOpen,
@@ -6,12 +6,13 @@ import {
   FolderPlus,
   Info,
   ExternalLink,
-  Star, Trash
+  Star, Trash, ImageIcon
 } from 'lucide-react';
 import {useAtom, useAtomValue} from "jotai";
 import {focusAtom} from "jotai-optics";
-import {categoriesAtom, settingsAtom} from "@/store/settings.js";
-import {libraryAtom, mangaListAtom, saveLibraryAtom} from "@/store/library.js";
+import {settingsAtom} from "@/store/settings.js";
+import {categoriesAtom} from "@/store/categories.js";
+import {loadLibraryAtom, mangaListAtom} from "@/store/library.js";
 import {nanoid} from 'nanoid';
 import {cn} from "@/lib/utils";
 
@@ -28,11 +29,14 @@ import {
 } from "@/components/ui/context-menu";
 import {Badge} from "@/components/ui/badge";
 import {convertFileSrc, invoke} from "@tauri-apps/api/core";
+import {open} from "@tauri-apps/plugin-dialog";
 import {
   isInLibrary,
   useMangaLibrary,
 } from "@/hooks/useMangaLibrary.js";
 
+const mangaCardSizeAtom = focusAtom(settingsAtom, optic => optic.prop("manga_card_size"));
+
 
 /**
  * MangaCard component displays a manga item with cover, title and context menu
@@ -47,6 +51,25 @@ const MangaCard = ({manga, onClick, isLibrary}) => {
   const navigate = useNavigate();
   const categories = useAtomValue(categoriesAtom);
   const mangaList = useAtomValue(mangaListAtom);
+  const cardSize = useAtomValue(mangaCardSizeAtom);
+  const [, loadLibrary] = useAtom(loadLibraryAtom);
+  // undefined while loading, null when only the full-size cover is available
+  const [thumbnail, setThumbnail] = useState(undefined);
+
+  // Local covers are shown through resized thumbnails instead of full-size pages
+  useEffect(() => {
+    if (manga.source_id !== "local" || !manga.cover) {
+      setThumbnail(null);
+      return;
+    }
+    setThumbnail(undefined);
+    invoke("get_manga_thumbnail", {mangaId: manga.id, size: cardSize})
+      .then(setThumbnail)
+      .catch((err) => {
+        console.error('Error loading thumbnail:', err);
+        setThumbnail(null);
+      });
+  }, [manga.id, manga.cover, manga.source_id, cardSize]);
 
   const {
     addMangaToLibrary,
@@ -78,10 +101,37 @@ const MangaCard = ({manga, onClick, isLibrary}) => {
     }
   };
 
+  // Replace the cover with an image file picked by the user
+  const handleChangeCover = async () => {
+    try {
+      const selected = await open({
+        multiple: false,
+        filters: [{
+          name: 'Image',
+          extensions: ['jpg', 'jpeg', 'png', 'webp', 'gif', 'bmp']
+        }],
+        title: 'Select Cover Image'
+      });
+
+      if (selected) {
+        await invoke("set_manga_cover", {
+          mangaId: manga.id,
+          source: {type: "file", path: selected.toString()},
+        });
+        await loadLibrary();
+      }
+    } catch (err) {
+      console.error('Error changing cover:', err);
+    }
+  };
+
   // Handle getting cover image based on source
   const getCoverImage = () => {
     if (manga.source_id === "local") {
-      return convertFileSrc(manga.cover)
+      if (thumbnail === undefined) {
+        return undefined;
+      }
+      return convertFileSrc(thumbnail ?? manga.cover)
     } else {
       return manga.cover;
     }
@@ -205,6 +255,12 @@ const MangaCard = ({manga, onClick, isLibrary}) => {
             ))}
           </ContextMenuSubContent>
         </ContextMenuSub>
+        {inLibrary && manga.source_id === "local" && (
+          <ContextMenuItem onClick={handleChangeCover}>
+            <ImageIcon className="mr-2 h-4 w-4"/>
+            <span>Change Cover</span>
+          </ContextMenuItem>
+        )}
         {inLibrary && (
           <>
             <ContextMenuSeparator/>
@@ -76,6 +76,7 @@ const DraggableMenuBar = ({
                             initialItem,
                             onItemSelect,
                             onItemsReordered,
+                            onItemAdd,
                             allowAddItem = false,
                             addItemTitle = "Add Item",
                             addItemPlaceholder = "Enter item name",
@@ -185,27 +186,37 @@ const DraggableMenuBar = ({
     }
   };
 
-  const handleAddItem = () => {
+  const handleAddItem = async () => {
     if (newItemName.trim()) {
-      // Create a slug-like ID from the name
-      const id = newItemName.trim()
-        .toLowerCase()
-        .replace(/[^a-z0-9]+/g, '-')
-        .replace(/(^-|-$)/g, '');
-
-      // Make sure ID doesn't already exist
-      const uniqueId = menuItems.some(item => item.id === id)
-        ? `${id}-${Date.now().toString(36)}`
-        : id;
-
-      const newItem = {
-        id: uniqueId,
-        name: newItemName.trim()
-      };
-
-      // Add new item and close dialog
-      if (onItemsReordered) {
-        onItemsReordered([...menuItems, newItem]);
+      let uniqueId;
+      if (onItemAdd) {
+        // The owner creates the item and hands back its ID; nothing is added if it refused
+        const newItem = await onItemAdd(newItemName.trim());
+        if (!newItem) {
+          return;
+        }
+        uniqueId = newItem.id;
+      } else {
+        // Create a slug-like ID from the name
+        const id = newItemName.trim()
+          .toLowerCase()
+          .replace(/[^a-z0-9]+/g, '-')
+          .replace(/(^-|-$)/g, '');
+
+        // Make sure ID doesn't already exist
+        uniqueId = menuItems.some(item => item.id === id)
+          ? `${id}-${Date.now().toString(36)}`
+          : id;
+
+        const newItem = {
+          id: uniqueId,
+          name: newItemName.trim()
+        };
+
+        // Add new item and close dialog
+        if (onItemsReordered) {
+          onItemsReordered([...menuItems, newItem]);
+        }
       }
 
       setNewItemName("");
@@ -1,13 +1,14 @@
 import React, {useState, useRef, useEffect} from 'react';
-import {X, Settings, HelpCircle} from 'lucide-react';
-import {useAtom} from 'jotai';
+import {X, Settings, HelpCircle, ImageIcon} from 'lucide-react';
+import {useAtom, useAtomValue} from 'jotai';
 import {cn} from '@/lib/utils';
 import HelpOverlay from "@/components/reader/HelpOverlay.jsx";
 import SettingsOverlay from "@/components/reader/SettingsOverlay.jsx";
 import {Button} from "@/components/ui/button.jsx";
 import {readerPageLayoutAtom, readerZoomAtom, readingModeAtom} from "@/store/settings.js";
 import {useNavigate, useLocation} from "react-router";
-import {convertFileSrc} from "@tauri-apps/api/core";
+import {convertFileSrc, invoke} from "@tauri-apps/api/core";
+import {loadLibraryAtom, mangaListAtom} from "@/store/library.js";
 import useFetchMangaChapters from "@/hooks/useFetchMangaChapters";
 
 const MangaReader = ({
@@ -17,7 +18,8 @@ const MangaReader = ({
                        chapterTitle = "",
                        onClose,
                        chapters = [],
-                       chapterId
+                       chapterId,
+                       initialScrollOffset = 0
                      }) => {
   const [readingMode, setReadingMode] = useAtom(readingModeAtom);
   const [readerPageLayout, setReaderPageLayout] = useAtom(readerPageLayoutAtom);
@@ -30,9 +32,69 @@ const MangaReader = ({
   const [currentPageIndex, setCurrentPageIndex] = useState(initialPage);
   const [showSettings, setShowSettings] = useState(false);
   const [showHelp, setShowHelp] = useState(false);
+  const [, loadLibrary] = useAtom(loadLibraryAtom);
 
   const containerRef = useRef(null);
   const webtoonRef = useRef(null);
+  const saveTimeoutRef = useRef(null);
+  const mangaList = useAtomValue(mangaListAtom);
+  const inLibrary = mangaList.some(m => m.id === manga?.id);
+
+  // Save the position once the reader settles, so paging quickly doesn't flood the backend
+  const scheduleProgressSave = () => {
+    if (!inLibrary || !pages?.pages?.length) return;
+    clearTimeout(saveTimeoutRef.current);
+    saveTimeoutRef.current = setTimeout(() => {
+      invoke("record_reading_progress", {
+        mangaId: manga.id,
+        chapterId: chapter?.id ?? chapterId ?? "",
+        page: currentPageIndex,
+        scrollOffset: webtoonRef.current?.scrollTop ?? 0,
+      }).catch((err) => console.error('Error saving reading progress:', err));
+    }, 500);
+  };
+
+  useEffect(() => {
+    scheduleProgressSave();
+  }, [manga?.id, chapter?.id, currentPageIndex, inLibrary]);
+
+  useEffect(() => () => clearTimeout(saveTimeoutRef.current), []);
+
+  // Reading session of the open chapter, written to the history once the chapter is left
+  const sessionRef = useRef(null);
+  useEffect(() => {
+    if (!manga?.id) return;
+    const session = {startedAt: new Date().toISOString(), pagesSeen: new Set()};
+    sessionRef.current = session;
+
+    return () => {
+      if (session.pagesSeen.size === 0) return;
+      invoke("record_history", {
+        entry: {
+          manga_id: manga.id,
+          manga_title: manga.title,
+          cover: manga.cover ?? "",
+          chapter_id: chapter?.id ?? chapterId ?? "",
+          chapter_title: chapter?.title ?? chapterTitle,
+          pages_read: session.pagesSeen.size,
+          started_at: session.startedAt,
+          ended_at: new Date().toISOString(),
+          source: manga.source_id ?? "local",
+        }
+      }).catch((err) => console.error('Error recording history:', err));
+    };
+  }, [manga?.id, chapter?.id]);
+
+  useEffect(() => {
+    sessionRef.current?.pagesSeen.add(currentPageIndex);
+  }, [currentPageIndex, chapter?.id]);
+
+  // Return to where a webtoon chapter was left off
+  useEffect(() => {
+    if (readingMode === 'webtoon' && webtoonRef.current && initialScrollOffset > 0) {
+      webtoonRef.current.scrollTop = initialScrollOffset;
+    }
+  }, [webtoonRef.current]);
 
   // Setup end detection for webtoon mode
   useEffect(() => {
@@ -40,6 +102,7 @@ const MangaReader = ({
       const webtoonElement = webtoonRef.current;
 
       const handleScroll = () => {
+        scheduleProgressSave();
         const isAtBottom =
           webtoonElement.scrollHeight - webtoonElement.scrollTop - webtoonElement.clientHeight < 50;
 
@@ -278,6 +341,19 @@ const MangaReader = ({
     return pages.pages[currentPageIndex];
   };
 
+  // Use the page on screen as the manga's cover
+  const handleSetCover = async () => {
+    try {
+      await invoke("set_manga_cover", {
+        mangaId: manga.id,
+        source: {type: "page", chapter_id: chapter?.id ?? chapterId, page: getCurrentPage()},
+      });
+      await loadLibrary();
+    } catch (err) {
+      console.error('Error setting cover:', err);
+    }
+  };
+
   if (!pages?.pages || pages.pages.length === 0) {
     return (
       <div className="h-full w-full flex justify-center items-center bg-background text-foreground">
@@ -316,7 +392,7 @@ const MangaReader = ({
               {pages.pages.map((page, index) => {
                 return <img
                   key={index}
-                  src={manga.source_id === "local" ? convertFileSrc(pages.base_url + "/" + page) : pages.base_url + "/" + page}
+                  src={manga.source_id === "local" ? convertFileSrc(pages.base_url + "/" + page, pages.protocol) : pages.base_url + "/" + page}
                   alt={`Page ${index + 1}`}
                   className="w-auto max-w-full object-contain"
                   style={{transform: `scale(${readerZoom})`, transformOrigin: 'top center'}}
@@ -341,7 +417,7 @@ const MangaReader = ({
                   {readingMode === 'right-to-left' ? (
                     <>
                       <img
-                        src={manga.source_id === "local" ? convertFileSrc(pages.base_url + "/" + getCurrentPage()) : pages.base_url + "/" + getCurrentPage()}
+                        src={manga.source_id === "local" ? convertFileSrc(pages.base_url + "/" + getCurrentPage(), pages.protocol) : pages.base_url + "/" + getCurrentPage()}
                         alt={`Page ${currentPageIndex + 1}`}
                         className="max-h-[calc(100vh-40px)] object-contain"
                       />
@@ -349,7 +425,7 @@ const MangaReader = ({
                       {currentPageIndex < pages.pages.length - 1 && (
                         <img
                           src={manga.source_id === "local" ?
-                            convertFileSrc(pages.base_url + "/" + pages.pages[currentPageIndex + 1]) :
+                            convertFileSrc(pages.base_url + "/" + pages.pages[currentPageIndex + 1], pages.protocol) :
                             pages.base_url + "/" + pages.pages[currentPageIndex + 1]}
                           alt={`Page ${currentPageIndex + 2}`}
                           className="max-h-[calc(100vh-40px)] object-contain"
@@ -361,7 +437,7 @@ const MangaReader = ({
                       {currentPageIndex > 0 && currentPageIndex % 2 === 1 && (
                         <img
                           src={manga.source_id === "local" ?
-                            convertFileSrc(pages.base_url + "/" + pages.pages[currentPageIndex - 1]) :
+                            convertFileSrc(pages.base_url + "/" + pages.pages[currentPageIndex - 1], pages.protocol) :
                             pages.base_url + "/" + pages.pages[currentPageIndex - 1]}
                           alt={`Page ${currentPageIndex}`}
                           className="max-h-[calc(100vh-40px)] object-contain"
@@ -370,7 +446,7 @@ const MangaReader = ({
 
                       <img
                         src={manga.source_id === "local" ?
-                          convertFileSrc(pages.base_url + "/" + getCurrentPage()) :
+                          convertFileSrc(pages.base_url + "/" + getCurrentPage(), pages.protocol) :
                           pages.base_url + "/" + getCurrentPage()}
                         alt={`Page ${currentPageIndex + 1}`}
                         className="max-h-[calc(100vh-40px)] object-contain"
@@ -381,7 +457,7 @@ const MangaReader = ({
               ) : (
                 <img
                   src={manga.source_id === "local" ?
-                    convertFileSrc(pages.base_url + "/" + getCurrentPage()) :
+                    convertFileSrc(pages.base_url + "/" + getCurrentPage(), pages.protocol) :
                     pages.base_url + "/" + getCurrentPage()}
                   alt={`Page ${currentPageIndex + 1}`}
                   className="max-h-full max-w-full object-contain"
@@ -438,6 +514,17 @@ const MangaReader = ({
           </div>
 
           <div className="flex items-center gap-2">
+            {manga.source_id === "local" && readingMode !== "webtoon" && (
+              <Button
+                onClick={handleSetCover}
+                size={"icon"}
+                variant={"ghost"}
+                className={"rounded-full p-2 text-foreground"}
+                title="Use page as cover"
+              >
+                <ImageIcon strokeWidth={1.5} className={"!w-5 !h-5"}/>
+              </Button>
+            )}
             <Button
               onClick={() => setShowHelp(true)}
               size={"icon"}
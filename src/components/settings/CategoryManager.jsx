@@ -1,7 +1,10 @@
 import React, { useState } from "react";
 import { useAtom } from "jotai";
 import { focusAtom } from "jotai-optics";
+import { invoke } from "@tauri-apps/api/core";
 import { settingsAtom, saveSettingsAtom } from "@/store/settings";
+import { categoriesAtom, loadCategoriesAtom } from "@/store/categories.js";
+import { loadLibraryAtom } from "@/store/library.js";
 import { DndContext, closestCenter, KeyboardSensor, PointerSensor, useSensor, useSensors } from "@dnd-kit/core";
 import { SortableContext, sortableKeyboardCoordinates, verticalListSortingStrategy, useSortable } from "@dnd-kit/sortable";
 import { restrictToVerticalAxis } from "@dnd-kit/modifiers";
@@ -19,9 +22,9 @@ import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
 import { GripVertical, Trash2, Pencil, X } from "lucide-react";
-const categoriesAtom = focusAtom(settingsAtom, optic => optic.prop("categories"));
+const defaultCategoryAtom = focusAtom(settingsAtom, optic => optic.prop("default_category"));
 
-const SortableItem = ({ category, onDelete, onEdit }) => {
+const SortableItem = ({ category, isOnly, onDelete, onEdit }) => {
   const [isEditing, setIsEditing] = useState(false);
   const [editedName, setEditedName] = useState(category.name);
 
@@ -112,7 +115,7 @@ const SortableItem = ({ category, onDelete, onEdit }) => {
           size="icon"
           onClick={() => onDelete(category.id)}
           className="text-destructive opacity-0 group-hover:opacity-100"
-          disabled={category.id === "all"} // Prevent deleting the "All" category
+          disabled={isOnly} // Manga always need a category to be in
         >
           <Trash2 size={16} />
         </Button>
@@ -123,7 +126,10 @@ const SortableItem = ({ category, onDelete, onEdit }) => {
 
 const CategoryManager = ({ open, onClose }) => {
   const [categories, setCategories] = useAtom(categoriesAtom);
+  const [defaultCategory, setDefaultCategory] = useAtom(defaultCategoryAtom);
   const [, saveSettings] = useAtom(saveSettingsAtom);
+  const [, loadCategories] = useAtom(loadCategoriesAtom);
+  const [, loadLibrary] = useAtom(loadLibraryAtom);
   const [newCategoryName, setNewCategoryName] = useState("");
 
   const sensors = useSensors(
@@ -133,7 +139,7 @@ const CategoryManager = ({ open, onClose }) => {
     })
   );
 
-  const handleDragEnd = (event) => {
+  const handleDragEnd = async (event) => {
     const { active, over } = event;
 
     if (active.id !== over.id) {
@@ -142,44 +148,61 @@ const CategoryManager = ({ open, onClose }) => {
 
       const newCategories = arrayMove(categories, oldIndex, newIndex);
       setCategories(newCategories);
-      setTimeout(() => saveSettings(), 0);
+      try {
+        setCategories(await invoke("reorder_categories", {
+          categoryIds: newCategories.map(cat => cat.id)
+        }));
+      } catch (error) {
+        console.error("Error reordering categories:", error);
+        await loadCategories();
+      }
     }
   };
 
-  const handleAddCategory = () => {
+  const handleAddCategory = async () => {
     if (newCategoryName.trim()) {
-      const id = newCategoryName.trim()
-        .toLowerCase()
-        .replace(/[^a-z0-9]+/g, '-')
-        .replace(/(^-|-$)/g, '');
-
-      const uniqueId = categories.some(cat => cat.id === id)
-        ? `${id}-${Date.now().toString(36)}`
-        : id;
-
-      const newCategory = {
-        id: uniqueId,
-        name: newCategoryName.trim()
-      };
-
-      setCategories([...categories, newCategory]);
-      setNewCategoryName("");
-      setTimeout(() => saveSettings(), 0);
+      try {
+        await invoke("create_category", {name: newCategoryName});
+        setNewCategoryName("");
+        await loadCategories();
+      } catch (error) {
+        console.error("Error creating category:", error);
+      }
     }
   };
 
-  const handleDeleteCategory = (id) => {
-    if (id === "all") return;
+  // Manga in a deleted category move to the default category, or the first one left.
+  // Deleting the default category makes that one the new default
+  const handleDeleteCategory = async (id) => {
+    const category = categories.find(cat => cat.id === id);
+    const others = categories.filter(cat => cat.id !== id);
+    const reassignTo = others.some(cat => cat.id === defaultCategory)
+      ? defaultCategory
+      : others[0]?.id;
 
-    setCategories(categories.filter(cat => cat.id !== id));
-    setTimeout(() => saveSettings(), 0);
+    try {
+      await invoke("delete_category", {
+        categoryId: id,
+        reassignTo: category?.manga_count > 0 ? reassignTo : null,
+      });
+      if (id === defaultCategory) {
+        setDefaultCategory(reassignTo);
+        await saveSettings();
+      }
+      await loadCategories();
+      await loadLibrary();
+    } catch (error) {
+      console.error("Error deleting category:", error);
+    }
   };
 
-  const handleEditCategory = (id, newName) => {
-    setCategories(categories.map(cat =>
-      cat.id === id ? { ...cat, name: newName } : cat
-    ));
-    setTimeout(() => saveSettings(), 0);
+  const handleEditCategory = async (id, newName) => {
+    try {
+      await invoke("rename_category", {categoryId: id, name: newName});
+      await loadCategories();
+    } catch (error) {
+      console.error("Error renaming category:", error);
+    }
   };
 
   const handleKeyDown = (e) => {
@@ -228,6 +251,7 @@ const CategoryManager = ({ open, onClose }) => {
                   <SortableItem
                     key={category.id}
                     category={category}
+                    isOnly={categories.length === 1}
                     onDelete={handleDeleteCategory}
                     onEdit={handleEditCategory}
                   />
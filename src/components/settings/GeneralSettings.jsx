@@ -10,10 +10,10 @@ import {Label} from "@/components/ui/label";
 import {useAtom, useAtomValue} from "jotai";
 import {focusAtom} from "jotai-optics";
 import {settingsAtom, saveSettingsAtom} from "@/store/settings";
+import {categoriesAtom} from "@/store/categories.js";
 
 const defaultCategoryTabAtom = focusAtom(settingsAtom, optic => optic.prop("default_category_tab"));
 const mangaCardSizeAtom = focusAtom(settingsAtom, optic => optic.prop("manga_card_size"));
-const categoriesAtom = focusAtom(settingsAtom, optic => optic.prop("categories"));
 const themeAtom = focusAtom(settingsAtom, optic => optic.prop("theme"))
 
 const GeneralSettings = () => {
@@ -11,9 +11,9 @@ import { Button } from "@/components/ui/button";
 import { useAtom } from "jotai";
 import { focusAtom } from "jotai-optics";
 import { settingsAtom, saveSettingsAtom } from "@/store/settings";
+import { categoriesAtom } from "@/store/categories.js";
 import CategoryManager from "@/components/settings/CategoryManager";
 
-const categoriesAtom = focusAtom(settingsAtom, optic => optic.prop("categories"));
 const selectedCategoryAtom = focusAtom(settingsAtom, optic => optic.prop("selected_category_tab"));
 const defaultCategoryAtom = focusAtom(settingsAtom, optic => optic.prop("default_category"));
 
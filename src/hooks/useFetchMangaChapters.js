@@ -15,12 +15,26 @@ const useFetchMangaChapters = (manga, offset = 0, sortOrder = 'desc') => {
       setLoading(true);
       try {
         if (manga.source_id === "local") {
-          const result = {}
-          result.id = nanoid();
-          result.number = 1;
-          result.title = "";
-          result.volume = "Local";
-          setChapters([result]);
+          if (manga.chapters?.length > 0) {
+            const resultChapters = manga.chapters.map((chapter) => {
+              const result = {}
+              result.id = chapter.id;
+              result.number = chapter.number;
+              result.pages = chapter.page_count;
+              result.title = chapter.title;
+              result.volume = chapter.volume;
+              result.path = chapter.path;
+              return result;
+            })
+            setChapters(resultChapters);
+          } else {
+            const result = {}
+            result.id = nanoid();
+            result.number = 1;
+            result.title = "";
+            result.volume = "Local";
+            setChapters([result]);
+          }
         } else {
           const extension = extensions.filter(ext => ext.id === manga.source_id)[0];
 
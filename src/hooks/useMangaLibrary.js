@@ -1,20 +1,14 @@
 import { invoke } from "@tauri-apps/api/core";
 import { useAtom } from "jotai";
-import { libraryAtom, saveLibraryAtom } from "@/store/library.js";
-import { focusAtom } from "jotai-optics";
+import { loadLibraryAtom } from "@/store/library.js";
 import { useCallback } from "react";
-import { nanoid } from "nanoid";
-
-// Create a focused atom for the manga list for better performance
-const mangaListAtom = focusAtom(libraryAtom, optic => optic.prop("manga"));
 
 /**
  * Custom hook for manga library operations
  * @returns {Object} Library operations
  */
 export function useMangaLibrary() {
-  const [mangaList, setMangaList] = useAtom(mangaListAtom);
-  const [, saveLibrary] = useAtom(saveLibraryAtom);
+  const [, loadLibrary] = useAtom(loadLibraryAtom);
 
   /**
    * Add a manga to the library
@@ -24,49 +18,26 @@ export function useMangaLibrary() {
    */
   const addMangaToLibrary = useCallback(async (manga, categoryId) => {
     try {
-      // Check if manga already exists
-      const existingManga = mangaList.find(m =>
-        (m.id === manga.id) ||
-        (m.source_id === manga.source_id && m.title === manga.title)
-      );
-
-      if (existingManga) {
-        // Update existing manga
-        const updatedList = mangaList.map(m =>
-          m.id === existingManga.id
-            ? { ...m, category: categoryId, updated_at: new Date().toISOString() }
-            : m
-        );
-
-        setMangaList(updatedList);
-      } else {
-        // Create a new manga entry
-        const newManga = {
-          id: manga.id || nanoid(),
+      // The backend moves a manga that is already in the library to the category instead
+      await invoke("add_manga", {
+        mangaInput: {
+          id: manga.id,
           title: manga.title,
           cover: manga.cover,
           category: categoryId,
-          progress: 0,
-          last_read: null,
           path: manga.path || '',
           source_id: manga.source_id || 'local',
           description: manga.description || '',
-          created_at: new Date().toISOString(),
-          updated_at: new Date().toISOString()
-        };
-
-        setMangaList([...mangaList, newManga]);
-      }
-
-      // Save to storage
-      await saveLibrary();
+        }
+      });
+      await loadLibrary();
       return true;
 
     } catch (error) {
       console.error("Error adding manga to library:", error);
       return false;
     }
-  }, [mangaList, setMangaList, saveLibrary]);
+  }, [loadLibrary]);
 
   /**
    * Update a manga's category
@@ -76,36 +47,15 @@ export function useMangaLibrary() {
    */
   const updateMangaCategory = useCallback(async (manga, categoryId) => {
     try {
-      const mangaId = manga.id;
-
-      // Find manga in list
-      const mangaIndex = mangaList.findIndex(m => m.id === mangaId);
-
-      if (mangaIndex === -1) {
-        console.error(`Manga with id ${mangaId} not found`);
-        return false;
-      }
-
-      // Create updated list
-      const updatedList = [...mangaList];
-      updatedList[mangaIndex] = {
-        ...updatedList[mangaIndex],
-        category: categoryId,
-        updated_at: new Date().toISOString()
-      };
-
-      // Update state
-      setMangaList(updatedList);
-
-      // Save to storage
-      await saveLibrary();
+      await invoke("update_manga", {mangaId: manga.id, changes: {category: categoryId}});
+      await loadLibrary();
       return true;
 
     } catch (error) {
       console.error("Error updating manga category:", error);
       return false;
     }
-  }, [mangaList, setMangaList, saveLibrary]);
+  }, [loadLibrary]);
 
   /**
    * Delete a manga from the library
@@ -114,29 +64,16 @@ export function useMangaLibrary() {
    */
   const deleteMangaFromLibrary = useCallback(async (manga) => {
     try {
-      // Remove from state
-      const updatedList = mangaList.filter(m => m.id !== manga.id);
-      setMangaList(updatedList);
-
-      // If it's a local manga, delete the files
-      if (manga.source_id === 'local' && manga.path) {
-        try {
-          await invoke("delete_manga", { path: manga.path });
-        } catch (err) {
-          console.error("Error deleting manga files:", err);
-          // Continue with library update even if file deletion fails
-        }
-      }
-
-      // Save to storage
-      await saveLibrary();
+      // Imported files go with the entry; linked folders are left alone
+      await invoke("remove_manga", {mangaId: manga.id});
+      await loadLibrary();
       return true;
 
     } catch (error) {
       console.error("Error deleting manga:", error);
       return false;
     }
-  }, [mangaList, setMangaList, saveLibrary]);
+  }, [loadLibrary]);
 
   return {
     addMangaToLibrary,
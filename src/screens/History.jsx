@@ -1,18 +1,159 @@
-import {Snail} from "lucide-react";
-import React from "react";
+import {Snail, Trash2, EyeOff} from "lucide-react";
+import React, {useEffect, useState} from "react";
+import {invoke, convertFileSrc} from "@tauri-apps/api/core";
+import {useNavigate} from "react-router";
+import {useAtomValue} from "jotai";
+import {Button} from "@/components/ui/button.jsx";
+import {Switch} from "@/components/ui/switch.jsx";
+import {
+  DropdownMenu,
+  DropdownMenuContent,
+  DropdownMenuItem,
+  DropdownMenuTrigger
+} from "@/components/ui/dropdown-menu.jsx";
+import {mangaListAtom} from "@/store/library.js";
+
+const HISTORY_PAGE_SIZE = 50;
+
+// Start of the day `daysAgo` days back, in local time
+const startOfDay = (daysAgo) => {
+  const date = new Date();
+  date.setHours(0, 0, 0, 0);
+  date.setDate(date.getDate() - daysAgo);
+  return date.toISOString();
+};
 
 function History() {
+  const navigate = useNavigate();
+  const mangaList = useAtomValue(mangaListAtom);
+  const [entries, setEntries] = useState([]);
+  const [hasMore, setHasMore] = useState(false);
+  const [incognito, setIncognito] = useState(false);
+
+  const loadHistory = async (offset = 0) => {
+    try {
+      const page = await invoke("get_history", {limit: HISTORY_PAGE_SIZE, offset});
+      setEntries(prev => offset === 0 ? page : [...prev, ...page]);
+      setHasMore(page.length === HISTORY_PAGE_SIZE);
+    } catch (err) {
+      console.error('Error loading history:', err);
+    }
+  };
+
+  useEffect(() => {
+    loadHistory();
+    invoke("get_incognito").then(setIncognito);
+  }, []);
+
+  const handleIncognito = async (enabled) => {
+    await invoke("set_incognito", {enabled});
+    setIncognito(enabled);
+  };
+
+  const handleDeleteEntry = async (entry) => {
+    try {
+      await invoke("delete_history_entry", {id: entry.id});
+      setEntries(prev => prev.filter(e => e.id !== entry.id));
+    } catch (err) {
+      console.error('Error deleting history entry:', err);
+    }
+  };
+
+  const handleClear = async (from) => {
+    try {
+      await invoke("delete_history_range", {from, to: null});
+      await loadHistory();
+    } catch (err) {
+      console.error('Error clearing history:', err);
+    }
+  };
+
+  // Entries of manga still in the library open their details page
+  const handleOpen = (entry) => {
+    const manga = mangaList.find(m => m.id === entry.manga_id);
+    if (manga) {
+      navigate('/manga', {state: {manga}});
+    }
+  };
+
+  // Group sessions under the day they ended on
+  const days = entries.reduce((groups, entry) => {
+    const day = new Date(entry.ended_at).toLocaleDateString();
+    (groups[day] ??= []).push(entry);
+    return groups;
+  }, {});
+
   return (
-    <div className="flex flex-1">
-      <div
-        className="flex h-full w-full flex-1 flex-col justify-center m-auto gap-2 p-2 md:p-10">
-        <div className="flex flex-col items-center">
-          <Snail size={250} strokeWidth={0.25}/>
-          <h1>Start reading to see your reading history here.</h1>
-        </div>
+    <div className="flex flex-1 flex-col">
+      <div className="flex items-center justify-end gap-4 p-2">
+        <label className="flex items-center gap-2 text-sm text-muted-foreground">
+          <EyeOff size={16}/>
+          Incognito
+          <Switch checked={incognito} onCheckedChange={handleIncognito}/>
+        </label>
+        <DropdownMenu>
+          <DropdownMenuTrigger asChild>
+            <Button variant="outline" size="sm" disabled={entries.length === 0}>
+              <Trash2 className="mr-2 h-4 w-4"/>
+              Clear
+            </Button>
+          </DropdownMenuTrigger>
+          <DropdownMenuContent>
+            <DropdownMenuItem onClick={() => handleClear(startOfDay(0))}>Today</DropdownMenuItem>
+            <DropdownMenuItem onClick={() => handleClear(startOfDay(7))}>Last 7 days</DropdownMenuItem>
+            <DropdownMenuItem onClick={() => handleClear(null)}>All history</DropdownMenuItem>
+          </DropdownMenuContent>
+        </DropdownMenu>
       </div>
+
+      {entries.length === 0 ? (
+        <div
+          className="flex h-full w-full flex-1 flex-col justify-center m-auto gap-2 p-2 md:p-10">
+          <div className="flex flex-col items-center">
+            <Snail size={250} strokeWidth={0.25}/>
+            <h1>Start reading to see your reading history here.</h1>
+          </div>
+        </div>
+      ) : (
+        <div className="flex flex-col gap-6 p-2 overflow-auto no-scrollbar">
+          {Object.entries(days).map(([day, dayEntries]) => (
+            <div key={day} className="flex flex-col gap-2">
+              <h2 className="text-sm font-medium text-muted-foreground">{day}</h2>
+              {dayEntries.map(entry => (
+                <div key={entry.id} className="flex items-center gap-3 rounded-md p-2 hover:bg-accent">
+                  <img
+                    src={entry.source === "local" && entry.cover ? convertFileSrc(entry.cover) : entry.cover}
+                    alt={entry.manga_title}
+                    className="h-16 w-11 rounded object-cover cursor-pointer"
+                    onClick={() => handleOpen(entry)}
+                    onError={(e) => {
+                      e.target.onerror = null;
+                      e.target.src = 'https://placehold.co/400x600?text=No+Cover';
+                    }}
+                  />
+                  <div className="flex flex-1 flex-col cursor-pointer" onClick={() => handleOpen(entry)}>
+                    <span className="font-medium">{entry.manga_title}</span>
+                    <span className="text-sm text-muted-foreground">
+                      {entry.chapter_title || "Chapter"} · {entry.pages_read} pages ·{" "}
+                      {new Date(entry.ended_at).toLocaleTimeString([], {hour: '2-digit', minute: '2-digit'})}
+                    </span>
+                  </div>
+                  <Button variant="ghost" size="icon" onClick={() => handleDeleteEntry(entry)}>
+                    <Trash2 className="h-4 w-4"/>
+                  </Button>
+                </div>
+              ))}
+            </div>
+          ))}
+          {hasMore && (
+            <Button variant="outline" onClick={() => loadHistory(entries.length)}>
+              Load more
+            </Button>
+          )}
+        </div>
+      )}
     </div>
   )
 }
 
-export default History;
\ No newline at end of file
+export default History;
@@ -1,14 +1,28 @@
-import React, {useState} from "react";
-import {Origami, Plus, FolderUp, Archive, X} from "lucide-react";
+import React, {useEffect, useState} from "react";
+import {
+  Origami, Plus, FolderUp, FolderSymlink, Archive, FileSymlink, Layers, X, Search
+} from "lucide-react";
 import {open} from '@tauri-apps/plugin-dialog';
 import {invoke} from '@tauri-apps/api/core';
-import {useAtom} from "jotai";
+import {listen} from '@tauri-apps/api/event';
+import {nanoid} from "nanoid";
+import {useAtom, useAtomValue} from "jotai";
 import {focusAtom} from "jotai-optics";
 
-import {settingsAtom, saveSettingsAtom} from "@/store/settings";
-import {libraryAtom, loadLibraryAtom} from "@/store/library.js";
+import {settingsAtom, showNsfwAtom} from "@/store/settings";
+import {libraryRevisionAtom, loadLibraryAtom} from "@/store/library.js";
+import {categoriesAtom, loadCategoriesAtom} from "@/store/categories.js";
+import {extensionsAtom} from "@/store/extensions.js";
 
 import {Button} from "@/components/ui/button";
+import {Input} from "@/components/ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
 import {
   Tooltip,
   TooltipProvider,
@@ -17,29 +31,100 @@ import {
 import DraggableMenuBar from "@/components/library/DraggableMenuBar";
 import MangaCard from "@/components/base/MangaCard.jsx";
 
-const categoriesAtom = focusAtom(settingsAtom, optic => optic.prop("categories"));
 const defaultCategoryAtom = focusAtom(settingsAtom, optic => optic.prop("default_category"));
-const mangaAtom = focusAtom(libraryAtom, optic => optic.prop("manga"));
+
+const LIBRARY_PAGE_SIZE = 100;
 
 function Library() {
-  const [mangaList, setMangaList] = useAtom(mangaAtom);
+  const libraryRevision = useAtomValue(libraryRevisionAtom);
+  const extensions = useAtomValue(extensionsAtom);
+  const showNsfw = useAtomValue(showNsfwAtom);
   const [isMenuOpen, setIsMenuOpen] = useState(false);
 
   const [categories, setCategories] = useAtom(categoriesAtom);
   const [defaultCategory] = useAtom(defaultCategoryAtom);
-  const [, saveSettings] = useAtom(saveSettingsAtom);
+  const [, loadCategories] = useAtom(loadCategoriesAtom);
   const [, loadLibrary] = useAtom(loadLibraryAtom);
 
   const [selectedCategory, setSelectedCategory] = useState(defaultCategory);
+  // Imports made from the "all" tab go to the default category
+  const importCategory = selectedCategory === "all" ? defaultCategory : selectedCategory;
+  const [importProgress, setImportProgress] = useState(null);
+  const [search, setSearch] = useState("");
+  const [sort, setSort] = useState("title");
+  const [readStatus, setReadStatus] = useState("all");
+  const [source, setSource] = useState("all");
+  const [results, setResults] = useState({manga: [], total: 0});
+  const isFiltered = search.trim() !== "" || readStatus !== "all" || source !== "all";
+
+  // Filtering and sorting happen in the backend, one page at a time
+  const queryLibrary = async (offset = 0) => {
+    try {
+      const page = await invoke("query_library", {
+        query: {
+          search: search.trim() || null,
+          category: selectedCategory === "all" ? null : selectedCategory,
+          source: source === "all" ? null : source,
+          read_status: readStatus === "all" ? null : readStatus,
+          nsfw: showNsfw ? null : false,
+          sort,
+          limit: LIBRARY_PAGE_SIZE,
+          offset,
+        }
+      });
+      setResults(prev => offset === 0 ? page : {...page, manga: [...prev.manga, ...page.manga]});
+    } catch (err) {
+      console.error('Error querying library:', err);
+    }
+  };
+
+  // Query again whenever the library changes or a filter does
+  useEffect(() => {
+    queryLibrary();
+  }, [libraryRevision, selectedCategory, search, sort, readStatus, source, showNsfw]);
+
+  // Run an import command while following its progress events
+  const runImport = async (command, mangaInput) => {
+    const importId = nanoid();
+    setImportProgress({import_id: importId, entries_processed: 0, bytes_written: 0, current_file: ""});
+
+    const unlisten = await listen("import-progress", (event) => {
+      if (event.payload.import_id === importId) {
+        setImportProgress(event.payload);
+      }
+    });
 
-  const handleAddMangaFolder = async () => {
+    try {
+      const result = await invoke(command, {mangaInput: {...mangaInput, import_id: importId}});
+      if (result.duplicate_of) {
+        console.warn(`Imported content is already in the library as "${result.duplicate_of.title}"`);
+      }
+      if (result.skipped.length > 0) {
+        console.warn(`Skipped ${result.skipped.length} files during import:`, result.skipped);
+      }
+      return result;
+    } finally {
+      unlisten();
+      setImportProgress(null);
+    }
+  };
+
+  const handleCancelImport = async () => {
+    try {
+      await invoke("cancel_import", {importId: importProgress.import_id});
+    } catch (err) {
+      console.error('Error cancelling import:', err);
+    }
+  };
+
+  const handleAddMangaFolder = async (link = false) => {
     try {
       setIsMenuOpen(false); // Close the menu
 
       const selected = await open({
         directory: true,
         multiple: false,
-        title: 'Select Manga Folder'
+        title: link ? 'Select Manga Folder to Link' : 'Select Manga Folder'
       });
 
       if (selected) {
@@ -49,10 +134,11 @@ function Library() {
         const mangaInput = {
           title: folderName,
           path: folderPath,
-          category: selectedCategory,
+          category: importCategory,
+          link,
         };
 
-        await invoke("import_manga_folder", {mangaInput})
+        await runImport("import_manga_folder", mangaInput)
         await loadLibrary();
       }
     } catch (err) {
@@ -60,31 +146,75 @@ function Library() {
     }
   };
 
-  const handleAddMangaCBZ = async () => {
+  const handleAddMangaSeries = async () => {
     try {
       setIsMenuOpen(false); // Close the menu
 
       const selected = await open({
+        directory: true,
         multiple: false,
-        filters: [{
+        title: 'Select Folder of Volume Archives'
+      });
+
+      if (selected) {
+        const folderPath = selected.toString();
+        const folderName = folderPath.split('/').pop().split('\\').pop();
+
+        const mangaInput = {
+          title: folderName,
+          path: folderPath,
+          category: importCategory,
+        };
+
+        await runImport("import_manga_series", mangaInput)
+        await loadLibrary();
+      }
+    } catch (err) {
+      console.error('Error adding manga series:', err);
+    }
+  };
+
+  const handleAddMangaCBZ = async (link = false) => {
+    try {
+      setIsMenuOpen(false); // Close the menu
+
+      // Only CBZ and CBR files can be read in place
+      const selected = await open({
+        multiple: false,
+        filters: link ? [{
+          name: 'Comic Book Archive',
+          extensions: ['cbz', 'cbr']
+        }] : [{
           name: 'Comic Book Archive',
-          extensions: ['cbz']
+          extensions: ['cbz', 'cbr', 'cb7', 'cbt']
+        }, {
+          name: 'PDF',
+          extensions: ['pdf']
+        }, {
+          name: 'EPUB',
+          extensions: ['epub']
         }],
-        title: 'Select Manga CBZ File'
+        title: link ? 'Select Manga Archive to Link' : 'Select Manga Archive'
       });
 
       if (selected) {
         const filePath = selected.toString();
         const fileName = filePath.split('/').pop().split('\\').pop();
-        const title = fileName.replace(/\.cbz$/i, '');
+        const title = fileName.replace(/\.(cb[zr7t]|pdf|epub)$/i, '');
+        const command = /\.pdf$/i.test(fileName) ? "import_manga_pdf"
+          : /\.epub$/i.test(fileName) ? "import_manga_epub"
+          : "import_manga_archive";
 
         const mangaInput = {
           title: title,
           path: filePath,
-          category: selectedCategory,
+          category: importCategory,
+          // CBZs are kept as-is and read page by page instead of being extracted
+          keep_archive: /\.cbz$/i.test(fileName),
+          link,
         };
 
-        await invoke("import_manga_cbz", {mangaInput})
+        await runImport(command, mangaInput)
         await loadLibrary();
 
       }
@@ -101,14 +231,29 @@ function Library() {
     setSelectedCategory(categoryId);
   };
 
-  const handleCategoriesReordered = (newCategories) => {
+  const handleCategoriesReordered = async (newCategories) => {
     setCategories(newCategories);
-    setTimeout(() => saveSettings(), 0);
+    try {
+      setCategories(await invoke("reorder_categories", {
+        categoryIds: newCategories.map(category => category.id)
+      }));
+    } catch (err) {
+      console.error('Error reordering categories:', err);
+      await loadCategories();
+    }
+  };
+
+  const handleCategoryAdd = async (name) => {
+    try {
+      const category = await invoke("create_category", {name});
+      await loadCategories();
+      return category;
+    } catch (err) {
+      console.error('Error creating category:', err);
+      return null;
+    }
   };
 
-  const filteredManga = selectedCategory === "all" && mangaList.length !== 0
-    ? mangaList
-    : mangaList.filter(manga => manga.category === selectedCategory);
 
   return (
     <div className="flex flex-1 flex-col w-full">
@@ -117,31 +262,106 @@ function Library() {
         initialItem={selectedCategory}
         onItemSelect={handleCategorySelect}
         onItemsReordered={handleCategoriesReordered}
+        onItemAdd={handleCategoryAdd}
         allowAddItem={true}
         addItemTitle="Add New Category"
         addItemPlaceholder="Enter category name"
       />
-      <div className={"flex-1 overflow-y-auto no-scrollbar p-4"}>
-        {filteredManga.length > 0 ? (
-          <div className="flex flex-wrap gap-3">
-            {filteredManga.map((manga) => (
-              <MangaCard
-                key={manga.id}
-                manga={manga}
-                isLibrary={true}
-              />
+      <div className="flex items-center gap-2 px-4 pt-4">
+        <div className="relative flex-1">
+          <Search className="absolute left-2 top-2.5 h-4 w-4 text-muted-foreground"/>
+          <Input
+            placeholder="Search titles, authors and tags"
+            value={search}
+            onChange={(e) => setSearch(e.target.value)}
+            className="pl-8"
+          />
+        </div>
+        <Select value={sort} onValueChange={setSort}>
+          <SelectTrigger className="w-40">
+            <SelectValue placeholder="Sort by"/>
+          </SelectTrigger>
+          <SelectContent>
+            <SelectItem value="title">Title</SelectItem>
+            <SelectItem value="last_read">Last read</SelectItem>
+            <SelectItem value="date_added">Date added</SelectItem>
+            <SelectItem value="unread">Unread chapters</SelectItem>
+          </SelectContent>
+        </Select>
+        <Select value={readStatus} onValueChange={setReadStatus}>
+          <SelectTrigger className="w-36">
+            <SelectValue placeholder="Read status"/>
+          </SelectTrigger>
+          <SelectContent>
+            <SelectItem value="all">Any status</SelectItem>
+            <SelectItem value="unread">Unread</SelectItem>
+            <SelectItem value="reading">Reading</SelectItem>
+            <SelectItem value="completed">Completed</SelectItem>
+          </SelectContent>
+        </Select>
+        <Select value={source} onValueChange={setSource}>
+          <SelectTrigger className="w-36">
+            <SelectValue placeholder="Source"/>
+          </SelectTrigger>
+          <SelectContent>
+            <SelectItem value="all">All sources</SelectItem>
+            <SelectItem value="local">Local</SelectItem>
+            {extensions.filter(extension => extension.id).map((extension) => (
+              <SelectItem key={extension.id} value={extension.id}>
+                {extension.name || extension.id}
+              </SelectItem>
             ))}
+          </SelectContent>
+        </Select>
+      </div>
+      <div className={"flex-1 overflow-y-auto no-scrollbar p-4"}>
+        {results.manga.length > 0 ? (
+          <div className="flex flex-col gap-4">
+            <div className="flex flex-wrap gap-3">
+              {results.manga.map((manga) => (
+                <MangaCard
+                  key={manga.id}
+                  manga={manga}
+                  isLibrary={true}
+                />
+              ))}
+            </div>
+            {results.manga.length < results.total && (
+              <Button variant="outline" onClick={() => queryLibrary(results.manga.length)}>
+                Load more
+              </Button>
+            )}
           </div>
         ) : (
           <div className="flex h-full w-full flex-1 flex-col justify-center m-auto gap-2 p-2 md:p-10">
             <div className="flex flex-col items-center">
               <Origami size={200} strokeWidth={0.25} className="mb-6"/>
-              <h1 className="mb-6 text-xl">Your library is empty</h1>
+              <h1 className="mb-6 text-xl">
+                {isFiltered ? "No manga match your search"
+                  : selectedCategory === "all" ? "Your library is empty"
+                  : "No manga in this category"}
+              </h1>
             </div>
           </div>
         )}
       </div>
 
+      {importProgress && (
+        <div className="fixed bottom-6 left-6 z-50 flex items-center gap-4 rounded-lg border bg-background p-4 shadow-lg">
+          <div className="flex flex-col text-sm">
+            <span className="font-medium">
+              Importing… {importProgress.entries_processed} files ({(importProgress.bytes_written / (1024 * 1024)).toFixed(1)} MB)
+            </span>
+            <span className="max-w-64 truncate text-muted-foreground">
+              {importProgress.current_file.split('/').pop().split('\\').pop()}
+            </span>
+          </div>
+          <Button onClick={handleCancelImport} size="sm" variant="outline">
+            Cancel
+          </Button>
+        </div>
+      )}
+
       <TooltipProvider>
         <div className="fixed bottom-6 right-6 flex flex-col-reverse items-center gap-4 z-50">
           <Button
@@ -162,7 +382,7 @@ function Library() {
             <Tooltip>
               <TooltipTrigger asChild>
                 <Button
-                  onClick={handleAddMangaFolder}
+                  onClick={() => handleAddMangaFolder(false)}
                   size="icon"
                   variant="outline"
                   className={`rounded-full h-12 w-12 shadow-md ${isMenuOpen ? 'transform translate-y-0' : 'transform translate-y-10'} transition-all duration-300`}
@@ -175,7 +395,20 @@ function Library() {
             <Tooltip>
               <TooltipTrigger asChild>
                 <Button
-                  onClick={handleAddMangaCBZ}
+                  onClick={() => handleAddMangaFolder(true)}
+                  size="icon"
+                  variant="outline"
+                  className={`rounded-full h-12 w-12 shadow-md ${isMenuOpen ? 'transform translate-y-0' : 'transform translate-y-10'} transition-all duration-300 delay-50`}
+                >
+                  <FolderSymlink className="h-5 w-5"/>
+                </Button>
+              </TooltipTrigger>
+            </Tooltip>
+
+            <Tooltip>
+              <TooltipTrigger asChild>
+                <Button
+                  onClick={() => handleAddMangaCBZ(false)}
                   size="icon"
                   variant="outline"
                   className={`rounded-full h-12 w-12 shadow-md ${isMenuOpen ? 'transform translate-y-0' : 'transform translate-y-10'} transition-all duration-300 delay-75`}
@@ -184,6 +417,32 @@ function Library() {
                 </Button>
               </TooltipTrigger>
             </Tooltip>
+
+            <Tooltip>
+              <TooltipTrigger asChild>
+                <Button
+                  onClick={() => handleAddMangaCBZ(true)}
+                  size="icon"
+                  variant="outline"
+                  className={`rounded-full h-12 w-12 shadow-md ${isMenuOpen ? 'transform translate-y-0' : 'transform translate-y-10'} transition-all duration-300 delay-75`}
+                >
+                  <FileSymlink className="h-5 w-5"/>
+                </Button>
+              </TooltipTrigger>
+            </Tooltip>
+
+            <Tooltip>
+              <TooltipTrigger asChild>
+                <Button
+                  onClick={handleAddMangaSeries}
+                  size="icon"
+                  variant="outline"
+                  className={`rounded-full h-12 w-12 shadow-md ${isMenuOpen ? 'transform translate-y-0' : 'transform translate-y-10'} transition-all duration-300 delay-100`}
+                >
+                  <Layers className="h-5 w-5"/>
+                </Button>
+              </TooltipTrigger>
+            </Tooltip>
           </div>
         </div>
       </TooltipProvider>
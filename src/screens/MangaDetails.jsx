@@ -1,4 +1,4 @@
-import React, {useState} from 'react';
+import React, {useEffect, useState} from 'react';
 import {useLocation, useNavigate} from 'react-router';
 import {useAtomValue} from 'jotai';
 import {
@@ -30,9 +30,9 @@ import {
   HoverCardTrigger
 } from '@/components/ui/hover-card';
 import {mangaListAtom} from "@/store/library.js";
-import {categoriesAtom} from "@/store/settings.js"
+import {categoriesAtom} from "@/store/categories.js"
 import {useMangaLibrary} from "@/hooks/useMangaLibrary.js";
-import {convertFileSrc} from "@tauri-apps/api/core";
+import {convertFileSrc, invoke} from "@tauri-apps/api/core";
 
 /**
  * Manga details page component
@@ -54,6 +54,11 @@ const MangaDetails = () => {
   const {data: details, error: detailsError, loading: detailsLoading} = useFetchMangaDetails(manga);
   const {chapters, error: chaptersError, loading: chaptersLoading} = useFetchMangaChapters(manga);
 
+  // Library manga carry their own metadata; details from the extension win once loaded
+  const authors = details?.authors?.length ? details.authors : manga?.metadata?.authors ?? [];
+  const artists = details?.artists?.length ? details.artists : manga?.metadata?.artists ?? [];
+  const description = manga?.description || manga?.metadata?.description;
+
   const handleSort = () => {
     setSortOrder(prevOrder => prevOrder === 'desc' ? 'asc' : 'desc');
   };
@@ -62,6 +67,33 @@ const MangaDetails = () => {
     (m.id === manga.id && m.source_id === manga.source_id)
   );
 
+  // Where the reader was left off, for library manga that have been opened before
+  const [readingProgress, setReadingProgress] = useState(null);
+  useEffect(() => {
+    if (!isInLibrary) return;
+    invoke("get_reading_progress", {mangaId: manga.id})
+      .then(setReadingProgress)
+      .catch((err) => console.error('Error loading reading progress:', err));
+  }, [manga?.id, isInLibrary]);
+
+  const continueTarget = readingProgress?.last_read && readingProgress.continue_reading;
+  // Chapterless local manga are saved under an empty chapter ID
+  const continueChapter = continueTarget && (
+    chapters.find(ch => ch.id === continueTarget.chapter_id) ??
+    (continueTarget.chapter_id === "" ? chapters[0] : undefined)
+  );
+
+  const handleContinueReading = () => {
+    navigate('/reader', {
+      state: {
+        manga,
+        chapter: continueChapter,
+        page: continueTarget.page,
+        scrollOffset: continueTarget.scroll_offset
+      }
+    });
+  };
+
   const handleReadChapter = (chapter) => {
     if (chapter.isLocal) {
       navigate('/reader', {
@@ -129,14 +161,25 @@ const MangaDetails = () => {
           </div>
 
           <div className="mt-4 flex flex-col gap-2">
-            <Button
-              className="w-full"
-              onClick={() => chapters.length > 0 && handleReadChapter(chapters[0])}
-              disabled={chapters.length === 0 || chaptersLoading}
-            >
-              <BookOpen className="mr-2 h-4 w-4"/>
-              Start Reading
-            </Button>
+            {continueChapter ? (
+              <Button
+                className="w-full"
+                onClick={handleContinueReading}
+                disabled={chaptersLoading}
+              >
+                <BookOpen className="mr-2 h-4 w-4"/>
+                Continue Reading ({readingProgress.progress}%)
+              </Button>
+            ) : (
+              <Button
+                className="w-full"
+                onClick={() => chapters.length > 0 && handleReadChapter(chapters[0])}
+                disabled={chapters.length === 0 || chaptersLoading}
+              >
+                <BookOpen className="mr-2 h-4 w-4"/>
+                Start Reading
+              </Button>
+            )}
 
             {isInLibrary ? (
               <Button variant="outline" className="w-full">
@@ -216,11 +259,11 @@ const MangaDetails = () => {
             </div>
 
             <div className="space-y-2 mb-4">
-              {details?.authors && details.authors.length > 0 ? (
+              {authors.length > 0 ? (
                 <div className="flex items-center">
                   <User className="h-4 w-4 mr-2 text-muted-foreground"/>
                   <span className="text-sm font-medium mr-2">Author:</span>
-                  <span className="text-sm">{details.authors.join(', ')}</span>
+                  <span className="text-sm">{authors.join(', ')}</span>
                 </div>
               ) : manga.source !== 'local' && detailsLoading ? (
                 <div className="flex items-center">
@@ -230,11 +273,11 @@ const MangaDetails = () => {
                 </div>
               ) : null}
 
-              {details?.artists && details.artists.length > 0 ? (
+              {artists.length > 0 ? (
                 <div className="flex items-center">
                   <User className="h-4 w-4 mr-2 text-muted-foreground"/>
                   <span className="text-sm font-medium mr-2">Artist:</span>
-                  <span className="text-sm">{details.artists.join(', ')}</span>
+                  <span className="text-sm">{artists.join(', ')}</span>
                 </div>
               ) : manga.source !== 'local' && detailsLoading ? (
                 <div className="flex items-center">
@@ -248,8 +291,8 @@ const MangaDetails = () => {
             <div className="mb-6">
               <h3 className="text-lg font-medium mb-2">Description</h3>
               <div className="text-sm text-muted-foreground">
-                {manga?.description ? (
-                  <p className="whitespace-pre-line">{manga.description}</p>
+                {description ? (
+                  <p className="whitespace-pre-line">{description}</p>
                 ) : manga.source !== 'local' && detailsLoading ? (
                   <div className="space-y-2">
                     <Skeleton className="w-full h-4"/>
@@ -53,7 +53,8 @@ const Reader = () => {
         pages={pages}
         manga={manga}
         onClose={handleClose}
-        initialPage={0}
+        initialPage={location.state?.page ?? 0}
+        initialScrollOffset={location.state?.scrollOffset ?? 0}
         chapters={chapters}
       />
     </div>
@@ -0,0 +1,14 @@
+import {invoke} from "@tauri-apps/api/core";
+import {atom} from "jotai";
+
+// Categories are owned by the backend; this is a read-only copy refreshed after every change
+const categoriesAtom = atom([]);
+
+const loadCategoriesAtom = atom(null, async (get, set) => {
+  const categories = await invoke("get_categories");
+  set(categoriesAtom, categories);
+})
+
+const initializeCategoriesAtom = loadCategoriesAtom;
+
+export {categoriesAtom, initializeCategoriesAtom, loadCategoriesAtom};
@@ -1,46 +1,22 @@
-import {exists, readTextFile, writeTextFile} from "@tauri-apps/plugin-fs";
-import {BaseDirectory} from "@tauri-apps/api/path";
+import {invoke} from "@tauri-apps/api/core";
 import {atom} from "jotai";
 import {focusAtom} from "jotai-optics";
 
+// The library is owned by the backend; this is a read-only copy refreshed after every change
 const defaultLibrary = {
   manga: [],
-  createdAt: Date.now(),
-  updatedAt: Date.now()
 }
 const libraryAtom = atom(defaultLibrary);
 const mangaListAtom = focusAtom(libraryAtom, optic=> optic.prop("manga"));
-
-const initializeLibraryAtom = atom(null, async (get, set) => {
-  const fileExists = await exists("library.json", {
-    baseDir: BaseDirectory.AppData,
-  });
-
-  if (!fileExists) {
-    await writeTextFile("library.json", JSON.stringify(defaultLibrary), {
-      baseDir: BaseDirectory.AppData,
-    });
-    set(libraryAtom, defaultLibrary);
-  } else {
-    const libraryFile = await readTextFile("library.json", {
-      baseDir: BaseDirectory.AppData,
-    })
-    const library = JSON.parse(libraryFile);
-    set(libraryAtom, library);
-  }
-})
+// Bumped after every change, so views paging through `query_library` know to query again
+const libraryRevisionAtom = atom(0);
 
 const loadLibraryAtom = atom(null, async (get, set) => {
-  const libraryData = await readTextFile("library.json", {
-    baseDir: BaseDirectory.AppData,
-  })
-  set(libraryAtom, JSON.parse(libraryData))
+  const library = await invoke("get_library");
+  set(libraryAtom, library);
+  set(libraryRevisionAtom, get(libraryRevisionAtom) + 1);
 })
 
-const saveLibraryAtom = atom(null, async (get, set) => {
-  await writeTextFile("library.json", JSON.stringify(get(libraryAtom)), {
-    baseDir: BaseDirectory.AppData,
-  });
-})
+const initializeLibraryAtom = loadLibraryAtom;
 
-export {libraryAtom, mangaListAtom,initializeLibraryAtom,loadLibraryAtom, saveLibraryAtom};
\ No newline at end of file
+export {libraryAtom, mangaListAtom, libraryRevisionAtom, initializeLibraryAtom, loadLibraryAtom};
@@ -11,13 +11,6 @@ export const defaultSettings = {
   default_category: "HjFCo9Zlih",
   selected_category_tab: "reading",
   manga_card_size: "default",
-  categories:[
-    {id: "HjFCo9Zlih", name: "Picked Up"},
-    {id: "M6wEcKUjJO", name: "Reading"},
-    {id: "NOQI3t7Xuo", name: "On Hold"},
-    {id: "LmGttS3i8c", name: "Completed"},
-    {id: "ut8dw483Cj", name: "Dropped"}
-  ],
   reading_mode:"left-to-right",
   reading_page_layout:"one-page",
   reader_zoom:1.0,
@@ -28,7 +21,6 @@ export const defaultSettings = {
 
 export const settingsAtom = atom(defaultSettings);
 export const showNsfwAtom = focusAtom(settingsAtom, optic => optic.prop("show_nsfw"));
-export const categoriesAtom = focusAtom(settingsAtom, optic => optic.prop("categories"));
 export const readingModeAtom = focusAtom(settingsAtom, optic => optic.prop("reading_mode"));
 export const readerPageLayoutAtom = focusAtom(settingsAtom, optic => optic.prop("reading_page_layout"));
 export const readerZoomAtom = focusAtom(settingsAtom, optic => optic.prop("reader_zoom"));